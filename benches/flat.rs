@@ -0,0 +1,149 @@
+//! Compares a tree of boxed nodes with the same tree compiled into a [`FlatTree`].
+//!
+//! Run with `cargo bench --bench flat`.
+
+fn main() {
+    bench::run()
+}
+
+#[cfg(feature = "events")]
+mod bench {
+    pub fn run() {
+        println!("flat trees are not available with the `events` feature");
+    }
+}
+
+#[cfg(not(feature = "events"))]
+mod bench {
+    use std::{hint::black_box, time::Instant};
+
+    use bhv::*;
+
+    /// A tree description, turned into both a boxed and a flat tree.
+    enum Gen {
+        Seq(Vec<Gen>),
+        Sel(Vec<Gen>),
+        Inv(Box<Gen>),
+        Repeat(Box<Gen>, u32),
+        Leaf(u64),
+    }
+
+    #[derive(Default)]
+    struct Agent {
+        counter: u64,
+        steps: u64,
+    }
+
+    /// A small deterministic pseudo-random generator, so that both trees get the same shape.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    fn generate(rng: &mut Rng, depth: u32, width: u32) -> Gen {
+        if depth == 0 {
+            return Gen::Leaf(rng.next() % 3);
+        }
+
+        match rng.next() % 8 {
+            0 => Gen::Inv(Box::new(generate(rng, depth - 1, width))),
+            1 => Gen::Repeat(Box::new(generate(rng, depth - 1, width)), 2),
+            2..=4 => Gen::Seq((0..width).map(|_| generate(rng, depth - 1, width)).collect()),
+            _ => Gen::Sel((0..width).map(|_| generate(rng, depth - 1, width)).collect()),
+        }
+    }
+
+    fn boxed(g: &Gen) -> Box<dyn Bhv<Context=Agent>> {
+        match g {
+            Gen::Seq(c) => Box::new(Seq::with_nodes(c.iter().map(boxed).collect())),
+            Gen::Sel(c) => Box::new(Sel::with_nodes(c.iter().map(boxed).collect())),
+            Gen::Inv(c) => Box::new(boxed(c).inv()),
+            Gen::Repeat(c, n) => Box::new(boxed(c).repeat(*n)),
+            Gen::Leaf(0) => Box::new(cond(|a: &Agent| !a.counter.is_multiple_of(3))),
+            Gen::Leaf(1) => Box::new(action(|a: &mut Agent| a.counter += 1)),
+            Gen::Leaf(_) => Box::new(wait_ticks(2)),
+        }
+    }
+
+    fn flat(b: &mut FlatTreeBuilder<Agent>, g: &Gen) -> FlatId {
+        match g {
+            Gen::Seq(c) => {
+                let c: Vec<_> = c.iter().map(|c| flat(b, c)).collect();
+                b.seq(c)
+            }
+            Gen::Sel(c) => {
+                let c: Vec<_> = c.iter().map(|c| flat(b, c)).collect();
+                b.sel(c)
+            }
+            Gen::Inv(c) => {
+                let c = flat(b, c);
+                b.inv(c)
+            }
+            Gen::Repeat(c, n) => {
+                let c = flat(b, c);
+                b.repeat(c, *n)
+            }
+            Gen::Leaf(0) => b.leaf(cond(|a: &Agent| !a.counter.is_multiple_of(3))),
+            Gen::Leaf(1) => b.leaf(action(|a: &mut Agent| a.counter += 1)),
+            Gen::Leaf(_) => b.leaf(wait_ticks(2)),
+        }
+    }
+
+    /// Tick the tree `ticks` times in a few rounds, returning the fastest round and the final state
+    /// of the agent.
+    fn time<B: Bhv<Context=Agent>>(bhv: B, ticks: u32) -> (f64, Agent) {
+        const ROUNDS: u32 = 5;
+
+        let mut runner = Runner::new(bhv);
+        let mut agent = Agent::default();
+        let mut best = f64::INFINITY;
+
+        for _ in 0..ROUNDS {
+            let start = Instant::now();
+
+            for _ in 0..ticks {
+                if runner.tick(&mut agent) != Status::Running {
+                    agent.steps += 1;
+                }
+
+                black_box(&agent);
+            }
+
+            best = best.min(start.elapsed().as_secs_f64());
+        }
+
+        (best, agent)
+    }
+
+    pub fn run() {
+        const TICKS: u32 = 100_000;
+
+        for (depth, width) in [(4, 4), (6, 4), (8, 3)] {
+            let g = generate(&mut Rng(0x2545_f491_4f6c_dd1d), depth, width);
+
+            let mut b = FlatTreeBuilder::new();
+            let root = flat(&mut b, &g);
+
+            let (flat_time, flat_agent) = time(b.build(root), TICKS);
+            let (boxed_time, boxed_agent) = time(boxed(&g), TICKS);
+
+            assert_eq!(boxed_agent.counter, flat_agent.counter);
+            assert_eq!(boxed_agent.steps, flat_agent.steps);
+
+            let per_tick = |t: f64| t * 1e9 / TICKS as f64;
+
+            println!(
+                "depth {depth}, width {width}: boxed {:.0} ns/tick, flat {:.0} ns/tick (speedup {:.2}x)",
+                per_tick(boxed_time),
+                per_tick(flat_time),
+                boxed_time / flat_time,
+            );
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Derive macros for the [`bhv`](https://docs.rs/bhv) crate.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitInt};
+
+/// Derive `bhv::Event` for a type.
+///
+/// For structs, this implements `bhv::EventType`, so that the whole type is a single kind of
+/// event. For enums, each variant gets its own `EventKind` and is named `Enum::Variant`, so that
+/// nodes can wait for a single variant through `BhvExt::wait_for_variant`. Enums without variants
+/// are accepted as well.
+///
+/// The kinds are based on the `TypeId` of the type by default. Use `#[event(id = N)]` on the type
+/// to base them on the number `N` instead, which stays the same between builds.
+#[proc_macro_derive(Event, attributes(event))]
+pub fn derive_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let id = match stable_id(&input) {
+        Ok(id) => id,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let data = match &input.data {
+        Data::Struct(_) => {
+            let id = id.map(|id| quote! { const STABLE_ID: Option<u64> = Some(#id); });
+
+            return quote! {
+                impl #impl_generics ::bhv::EventType for #name #ty_generics #where_clause {
+                    #id
+                }
+            }.into();
+        }
+        Data::Enum(data) => data,
+        Data::Union(_) => {
+            return syn::Error::new_spanned(&input.ident, "`Event` cannot be derived for unions")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    let patterns: Vec<_> = data.variants
+        .iter()
+        .map(|v| {
+            let ident = &v.ident;
+
+            match v.fields {
+                Fields::Named(_) => quote! { Self::#ident { .. } },
+                Fields::Unnamed(_) => quote! { Self::#ident(..) },
+                Fields::Unit => quote! { Self::#ident },
+            }
+        })
+        .collect();
+
+    let names = data.variants.iter().map(|v| format!("{}::{}", name, v.ident));
+    let kinds = (0..data.variants.len() as u32).map(|i| match id {
+        Some(id) => quote! { ::bhv::EventKind::stable_variant(#id, #i) },
+        None => quote! { ::bhv::EventKind::variant::<Self>(#i) },
+    });
+
+    let matches_kind = id.map(|id| quote! {
+        #[inline]
+        fn matches_kind(kind: ::bhv::EventKind) -> bool {
+            kind.stable_id() == Some(#id)
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::bhv::Event for #name #ty_generics #where_clause {
+            #[inline]
+            fn event_name(&self) -> &str {
+                match *self {
+                    #(#patterns => #names,)*
+                }
+            }
+
+            #[inline]
+            fn event_kind(&self) -> ::bhv::EventKind {
+                match *self {
+                    #(#patterns => #kinds,)*
+                }
+            }
+
+            #matches_kind
+        }
+    }.into()
+}
+
+/// The number given through `#[event(id = N)]`, if any.
+fn stable_id(input: &DeriveInput) -> syn::Result<Option<u64>> {
+    let mut id = None;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("event")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                let lit: LitInt = meta.value()?.parse()?;
+                id = Some(lit.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `id = <number>`"))
+            }
+        })?;
+    }
+
+    Ok(id)
+}
@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 #[allow(unused_imports)]
 use crate::events_impl::{
     core::{Bhv, Status},
-    events::{Event, UnitEventPump},
+    events::{Event, EventKind, UnitEventPump},
 };
 
 /// The type of the result of [`action`].
@@ -23,6 +23,27 @@ pub struct Cond<P, C>(P, PhantomData<C>)
 pub struct AsyncAction<A, C>(A, PhantomData<C>)
     where A: FnMut(&mut C) -> Status;
 
+/// The type of the result of [`on_event`].
+#[derive(Clone)]
+pub struct OnEvent<E, A, C>(A, PhantomData<(E, C)>)
+    where
+        E: Event,
+        A: FnMut(&E, &mut C);
+
+/// The type of the result of [`cond_event`].
+#[derive(Clone)]
+pub struct CondEvent<E, P, C>(P, PhantomData<(E, C)>)
+    where
+        E: Event,
+        P: Fn(&E, &C) -> bool;
+
+/// The type of the result of [`async_on_event`].
+#[derive(Clone)]
+pub struct AsyncOnEvent<E, A, C>(A, PhantomData<(E, C)>)
+    where
+        E: Event,
+        A: FnMut(&E, &mut C) -> Status;
+
 impl<A, C> Bhv for Action<A, C>
     where A: FnMut(&mut C) {
     type Context = C;
@@ -31,6 +52,10 @@ impl<A, C> Bhv for Action<A, C>
         self.0(ctx);
         Status::Success
     }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Action"
+    }
 }
 
 impl<P, C> Bhv for Cond<P, C>
@@ -44,6 +69,10 @@ impl<P, C> Bhv for Cond<P, C>
             Status::Failure
         }
     }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Cond"
+    }
 }
 
 impl<A, C> Bhv for AsyncAction<A, C>
@@ -53,8 +82,81 @@ impl<A, C> Bhv for AsyncAction<A, C>
     fn react(&mut self, _event: &dyn Event, ctx: &mut Self::Context) -> Status {
         self.0(ctx)
     }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "AsyncAction"
+    }
+}
+
+impl<E, A, C> Bhv for OnEvent<E, A, C>
+    where
+        E: Event,
+        A: FnMut(&E, &mut C) {
+    type Context = C;
+    #[inline]
+    fn should_react_to(&self, kind: EventKind) -> bool {
+        E::matches_kind(kind)
+    }
+    #[inline]
+    fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
+        match event.downcast_ref::<E>() {
+            Some(e) => {
+                self.0(e, ctx);
+                Status::Success
+            }
+            None => Status::Running,
+        }
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "OnEvent"
+    }
+}
+
+impl<E, P, C> Bhv for CondEvent<E, P, C>
+    where
+        E: Event,
+        P: Fn(&E, &C) -> bool {
+    type Context = C;
+    #[inline]
+    fn should_react_to(&self, kind: EventKind) -> bool {
+        E::matches_kind(kind)
+    }
+    #[inline]
+    fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
+        match event.downcast_ref::<E>() {
+            Some(e) if self.0(e, ctx) => Status::Success,
+            Some(_) => Status::Failure,
+            None => Status::Running,
+        }
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "CondEvent"
+    }
 }
 
+impl<E, A, C> Bhv for AsyncOnEvent<E, A, C>
+    where
+        E: Event,
+        A: FnMut(&E, &mut C) -> Status {
+    type Context = C;
+    #[inline]
+    fn should_react_to(&self, kind: EventKind) -> bool {
+        E::matches_kind(kind)
+    }
+    #[inline]
+    fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
+        match event.downcast_ref::<E>() {
+            Some(e) => self.0(e, ctx),
+            None => Status::Running,
+        }
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "AsyncOnEvent"
+    }
+}
 
 /// Adapt a function that returns nothing into a behavior, returning [`Status::Success`]
 /// on every call to [`Bhv::react`].
@@ -135,4 +237,89 @@ pub fn cond<P, C>(p: P) -> Cond<P, C>
 /// ```
 #[inline]
 pub fn async_action<A, C>(a: A) -> AsyncAction<A, C>
-    where A: FnMut(&mut C) -> Status { AsyncAction(a, PhantomData) }
\ No newline at end of file
+    where A: FnMut(&mut C) -> Status { AsyncAction(a, PhantomData) }
+
+/// Adapt a function reading events of type `E` into a behavior, returning [`Status::Success`]
+/// on every event of type `E`. Events of other types are ignored: the node does not react to them
+/// (see [`Bhv::should_react_to`]) and returns [`Status::Running`] if given one anyway.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// struct Damage(u32);
+/// impl EventType for Damage {}
+///
+/// let mut hit = on_event(|d: &Damage, hp: &mut u32| *hp -= d.0);
+/// let mut hp = 100;
+///
+/// assert!(!hit.should_react_to(().event_type()));
+/// assert_eq!(hit.react(&Damage(30), &mut hp), Status::Success);
+/// assert_eq!(hp, 70);
+/// ```
+#[inline]
+pub fn on_event<E, A, C>(a: A) -> OnEvent<E, A, C>
+    where
+        E: Event,
+        A: FnMut(&E, &mut C) { OnEvent(a, PhantomData) }
+
+/// Adapt a predicate on events of type `E` into a behavior, returning [`Status::Success`] if
+/// the predicate returns `true` and [`Status::Failure`] otherwise. Events of other types are
+/// ignored, as with [`on_event`].
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// #[derive(Event)]
+/// enum Input {
+///     Key(char),
+///     Exit,
+/// }
+///
+/// let mut is_quit = cond_event(|i: &Input, _: &()| matches!(i, Input::Key('q') | Input::Exit));
+///
+/// assert_eq!(is_quit.react(&Input::Key('q'), &mut ()), Status::Success);
+/// assert_eq!(is_quit.react(&Input::Key('w'), &mut ()), Status::Failure);
+/// assert!(is_quit.should_react_to(Input::Exit.event_type()));
+/// ```
+#[inline]
+pub fn cond_event<E, P, C>(p: P) -> CondEvent<E, P, C>
+    where
+        E: Event,
+        P: Fn(&E, &C) -> bool { CondEvent(p, PhantomData) }
+
+/// Wrap a function reading events of type `E` and returning a [`crate::Status`] into a behavior.
+/// Events of other types are ignored, as with [`on_event`].
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// struct Tick(u32);
+/// impl EventType for Tick {}
+///
+/// struct Exit;
+/// impl EventType for Exit {}
+///
+/// let sum_ticks = async_on_event(|t: &Tick, sum: &mut u32| {
+///     *sum += t.0;
+///     Status::Running
+/// });
+///
+/// let events: [&dyn Event; 4] = [&Tick(1), &Tick(2), &Exit, &Tick(3)];
+/// let mut sum = 0;
+///
+/// // the node keeps running until an event it does not react to comes
+/// sum_ticks.execute(events, &mut sum);
+///
+/// assert_eq!(sum, 3);
+/// ```
+#[inline]
+pub fn async_on_event<E, A, C>(a: A) -> AsyncOnEvent<E, A, C>
+    where
+        E: Event,
+        A: FnMut(&E, &mut C) -> Status { AsyncOnEvent(a, PhantomData) }
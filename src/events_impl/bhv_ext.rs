@@ -1,9 +1,10 @@
-use std::marker::PhantomData;
+use std::{borrow::Cow, marker::PhantomData};
 
 #[allow(unused_imports)]
 use crate::events_impl::{
     core::{Bhv, Status},
     decor::*,
+    record::Recorded,
     events::{Event, EventExt, EventType, EventTypeExt},
 };
 
@@ -174,6 +175,154 @@ pub trait BhvExt: Bhv + Sized {
         }
     }
 
+    /// Return a node that runs this node only after an event of the same variant as `variant` is
+    /// triggered. The data of `variant` itself is ignored, only its [`EventKind`](crate::EventKind)
+    /// is used. See [`Event`] on how to give each variant of an enum its own kind.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// #[derive(Event)]
+    /// enum Input {
+    ///     Key(char),
+    ///     Exit,
+    /// }
+    ///
+    /// let mut on_exit = action(|closed: &mut bool| *closed = true).wait_for_variant(Input::Exit);
+    ///
+    /// assert!(!on_exit.should_react_to(Input::Key('q').event_type()));
+    /// assert!(on_exit.should_react_to(Input::Exit.event_type()));
+    ///
+    /// let mut closed = false;
+    /// on_exit.react(&Input::Exit, &mut closed);
+    ///
+    /// assert!(closed);
+    /// ```
+    #[inline]
+    fn wait_for_variant<E: Event>(self, variant: E) -> WaitFor<Self, E> {
+        WaitFor {
+            bhv: self,
+            kind: variant.event_type(),
+            _tag: PhantomData,
+        }
+    }
+
+    /// Return a node that runs this node as is, but is named `label` when inspecting the tree
+    /// through [`NodeRef`](crate::NodeRef).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// let attack = action(|hp: &mut i32| *hp -= 10).label("attack");
+    ///
+    /// assert_eq!(attack.kind(), "Action");
+    /// assert_eq!(attack.name(), "attack");
+    /// ```
+    #[inline]
+    fn label(self, label: impl Into<Cow<'static, str>>) -> Label<Self> {
+        Label {
+            bhv: self,
+            label: label.into(),
+        }
+    }
+
+    /// Return a node that records the name of every event this node reacts to, along with the
+    /// status returned by each node of the tree. See [`replay`](crate::replay) to check a tree
+    /// against a recording.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// let mut tree = action(|v: &mut i32| *v += 1).recorded();
+    /// tree.react(&(), &mut 0);
+    ///
+    /// assert_eq!(tree.trace().ticks.len(), 1);
+    /// assert_eq!(tree.trace().ticks[0].status, Status::Success);
+    /// assert_eq!(tree.trace().ticks[0].visits, [Visit { id: NodeId(0), status: Status::Success }]);
+    /// ```
+    #[inline]
+    fn recorded(self) -> Recorded<Self> {
+        Recorded::new(self)
+    }
+
+    /// Return a node that reports every event this node and its descendants react to as spans and
+    /// events of the [`tracing`] crate. See [`Traced`](crate::Traced) for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    /// # use std::{fmt, sync::{Arc, Mutex}};
+    /// # use tracing::{field::{Field, Visit}, span, Event, Metadata, Subscriber};
+    /// #
+    /// # // a subscriber writing each span and event on its own line, indented by the spans it is in
+    /// # #[derive(Clone, Default)]
+    /// # struct Capture(Arc<Mutex<(Vec<String>, usize)>>);
+    /// #
+    /// # struct Fields(String);
+    /// #
+    /// # impl Visit for Fields {
+    /// #     fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+    /// #         self.0 += &format!(" {}={:?}", field, value);
+    /// #     }
+    /// # }
+    /// #
+    /// # impl Subscriber for Capture {
+    /// #     fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+    /// #     fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+    /// #         let mut fields = Fields(span.metadata().name().to_string());
+    /// #         span.record(&mut fields);
+    /// #         let mut c = self.0.lock().unwrap();
+    /// #         let line = format!("{}{}", "  ".repeat(c.1), fields.0);
+    /// #         c.0.push(line);
+    /// #         span::Id::from_u64(c.0.len() as u64)
+    /// #     }
+    /// #     fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+    /// #     fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+    /// #     fn event(&self, event: &Event<'_>) {
+    /// #         let mut fields = Fields(String::new());
+    /// #         event.record(&mut fields);
+    /// #         let mut c = self.0.lock().unwrap();
+    /// #         let line = format!("{}{}", "  ".repeat(c.1), fields.0.trim_start());
+    /// #         c.0.push(line);
+    /// #     }
+    /// #     fn enter(&self, _: &span::Id) { self.0.lock().unwrap().1 += 1; }
+    /// #     fn exit(&self, _: &span::Id) { self.0.lock().unwrap().1 -= 1; }
+    /// # }
+    ///
+    /// let mut tree = seq! {
+    ///     cond(|v: &i32| *v > 0).label("is positive"),
+    ///     action(|v: &mut i32| *v -= 1),
+    /// }
+    /// .traced();
+    ///
+    /// let capture = Capture::default();
+    /// let mut v = 5;
+    ///
+    /// tracing::subscriber::with_default(capture.clone(), || tree.react(&(), &mut v));
+    ///
+    /// assert_eq!(v, 4);
+    /// assert_eq!(capture.0.lock().unwrap().0, [
+    ///     r#"node kind="Seq" name="Seq" path=[] event="()""#,
+    ///     r#"  node kind="Cond" name="is positive" path=[0] event="()""#,
+    ///     r#"    message=status status=Success"#,
+    ///     r#"  node kind="Action" name="Action" path=[1] event="()""#,
+    ///     r#"    message=status status=Success"#,
+    ///     r#"  message=status status=Success"#,
+    /// ]);
+    /// ```
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn traced(self) -> crate::Traced<Self> {
+        crate::Traced(self)
+    }
+
     /// Execute the node until it does not return [`Status::Running`] anymore. Events are consumed from `events`.
     fn execute<'a>(
         mut self,
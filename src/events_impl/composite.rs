@@ -1,9 +1,13 @@
+use std::fmt;
+
 #[allow(unused_imports)]
 use crate::events_impl::{
     adapt::action,
     core::{Bhv, Status},
     events::{Event, EventExt, EventKind},
+    record,
 };
+use crate::introspect::NodeRef;
 
 /// A selector is a behavior node composed of a list of nodes that are run until one of them succeeds,
 /// in which case the node also succeeds. If none of the nodes succeeds, this node fails.
@@ -13,17 +17,32 @@ pub struct Sel<C>(pub(crate) Box<[Box<dyn Bhv<Context=C>>]>);
 /// in which case the node also fails. If none of the nodes fails, this node succeeds.
 pub struct Seq<C>(pub(crate) Box<[Box<dyn Bhv<Context=C>>]>);
 
+impl<C> Sel<C> {
+    #[inline]
+    pub fn with_nodes(nodes: Vec<Box<dyn Bhv<Context=C>>>) -> Self {
+        Self(nodes.into_boxed_slice())
+    }
+}
+
+impl<C> Seq<C> {
+    #[inline]
+    pub fn with_nodes(nodes: Vec<Box<dyn Bhv<Context=C>>>) -> Self {
+        Self(nodes.into_boxed_slice())
+    }
+}
+
 impl<C> Bhv for Sel<C> {
     type Context = C;
     #[inline]
     fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
         let et = event.event_type();
 
-        for node in self
+        for (i, node) in self
             .0
             .iter_mut()
-            .take_while(|n| n.should_react_to(et)) {
-            let s = node.react(event, ctx);
+            .take_while(|n| n.should_react_to(et))
+            .enumerate() {
+            let s = record::react(i, node, event, ctx);
 
             if s != Status::Failure {
                 return s;
@@ -32,6 +51,18 @@ impl<C> Bhv for Sel<C> {
 
         Status::Failure
     }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.iter_mut().for_each(|n| n.halt(ctx))
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Sel"
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.0.iter().for_each(|n| f(NodeRef::new(n)))
+    }
 }
 
 impl<C> Bhv for Seq<C> {
@@ -41,10 +72,11 @@ impl<C> Bhv for Seq<C> {
         let et = event.event_type();
         let mut count = 0;
 
-        for node in self.0
+        for (i, node) in self.0
             .iter_mut()
-            .take_while(|n| n.should_react_to(et)) {
-            let s = node.react(event, ctx);
+            .take_while(|n| n.should_react_to(et))
+            .enumerate() {
+            let s = record::react(i, node, event, ctx);
 
             if s != Status::Success {
                 return s;
@@ -59,6 +91,30 @@ impl<C> Bhv for Seq<C> {
             Status::Success
         }
     }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.iter_mut().for_each(|n| n.halt(ctx))
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Seq"
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.0.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+}
+
+impl<C> fmt::Debug for Sel<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<C> fmt::Debug for Seq<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
 }
 
 /// A macro used to create a selector from a list of behaviors.
@@ -86,8 +142,8 @@ macro_rules! sel {
         compile_error!("`sel` should have at least one argument!")
     };
     ($($x:expr),+$(,)?) => {
-        $crate::Sel(
-            Box::new([$(Box::new($x)),+]),
+        $crate::Sel::with_nodes(
+            vec![$(Box::new($x)),+],
         )
     };
 }
@@ -113,8 +169,8 @@ macro_rules! seq {
         compile_error!("`seq` should have at least one argument!")
     };
     ($($x:expr),+$(,)?) => {
-        $crate::Seq(
-            Box::new([$(Box::new($x)),+]),
+        $crate::Seq::with_nodes(
+            vec![$(Box::new($x)),+],
         )
     };
 }
\ No newline at end of file
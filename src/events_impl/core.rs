@@ -1,7 +1,11 @@
+use std::borrow::Cow;
+
 use crate::events_impl::events::{Event, EventKind};
+use crate::introspect::NodeRef;
 
 /// An enum type representing the outcome of calling [`Bhv::update`].
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Status {
     /// The behavior is still running.
     Running,
@@ -18,7 +22,97 @@ pub trait Bhv {
     type Context;
     /// Check whether the node should run in response to events of the given kind.
     #[inline]
-    fn should_react_to(&self, kind: EventKind) -> bool { true }
+    fn should_react_to(&self, _kind: EventKind) -> bool { true }
     /// Run this node in response to an outer event.
     fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status;
-}
\ No newline at end of file
+    /// Interrupt the node while it is running, such as when a higher-priority branch of the tree
+    /// takes over, and bring it back to its initial state.
+    /// Composite and decorator nodes forward this to their children.
+    /// Defaults to nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// // A long-running action that needs to stop the motors when interrupted.
+    /// struct MoveForward;
+    ///
+    /// impl Bhv for MoveForward {
+    ///     type Context = bool;
+    ///     fn react(&mut self, _event: &dyn Event, moving: &mut bool) -> Status {
+    ///         *moving = true;
+    ///         Status::Running
+    ///     }
+    ///     fn halt(&mut self, moving: &mut bool) {
+    ///         *moving = false;
+    ///     }
+    /// }
+    ///
+    /// let mut tree = seq! { MoveForward }.repeat_until_pass();
+    /// let mut moving = false;
+    ///
+    /// tree.react(&(), &mut moving);
+    /// assert!(moving);
+    ///
+    /// tree.halt(&mut moving);
+    /// assert!(!moving);
+    /// ```
+    #[inline]
+    fn halt(&mut self, _ctx: &mut Self::Context) {}
+
+    /// A short name of the type of this node, such as `"Seq"` or `"Repeat"`.
+    /// Defaults to `"Leaf"`.
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Leaf"
+    }
+
+    /// A human-readable description of this node, such as `"Repeat(3)"` or a label given through
+    /// [`crate::BhvExt::label`].
+    /// Defaults to [`Bhv::kind`].
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.kind())
+    }
+
+    /// Call `f` on each child of this node, in order. Nodes that have children should override
+    /// this, so that the structure of the tree can be inspected through [`NodeRef`].
+    /// Defaults to nothing.
+    #[inline]
+    fn visit_children<'a>(&'a self, _f: &mut dyn FnMut(NodeRef<'a>)) {}
+}
+
+impl<B: Bhv + ?Sized> Bhv for Box<B> {
+    type Context = B::Context;
+
+    #[inline]
+    fn should_react_to(&self, kind: EventKind) -> bool {
+        (**self).should_react_to(kind)
+    }
+
+    #[inline]
+    fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
+        (**self).react(event, ctx)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        (**self).halt(ctx)
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        (**self).kind()
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        (**self).name()
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        (**self).visit_children(f)
+    }
+}
@@ -1,9 +1,11 @@
-use std::marker::PhantomData;
+use std::{borrow::Cow, marker::PhantomData};
 
 use crate::events_impl::{
     core::{Bhv, Status},
-    events::{Event, EventKind, EventType},
+    events::{Event, EventKind},
+    record,
 };
+use crate::introspect::NodeRef;
 
 /// A decorator that runs the given node until it's done and inverts
 /// the result.
@@ -44,14 +46,21 @@ pub struct RepeatUntilPass<B: Bhv>(pub(crate) B);
 #[derive(Clone)]
 pub struct RepeatUntilFail<B: Bhv>(pub(crate) B);
 
-/// A decorator that runs a node only when a certain event type is triggered.
+/// A decorator that runs a node only when a certain event type, or variant of an enum event, is
+/// triggered.
 #[derive(Clone)]
-pub struct WaitFor<B: Bhv, E: EventType> {
+pub struct WaitFor<B: Bhv, E: Event> {
     pub(crate) bhv: B,
     pub(crate) kind: EventKind,
     pub(crate) _tag: PhantomData<E>,
 }
 
+/// A decorator that runs the given node as is, but gives it a custom name when inspecting the tree.
+pub struct Label<B: Bhv> {
+    pub(crate) bhv: B,
+    pub(crate) label: Cow<'static, str>,
+}
+
 impl<B: Bhv> Bhv for Inv<B> {
     type Context = B::Context;
     #[inline]
@@ -60,12 +69,24 @@ impl<B: Bhv> Bhv for Inv<B> {
     }
     #[inline]
     fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
-        match self.0.react(event, ctx) {
+        match record::react(0, &mut self.0, event, ctx) {
             Status::Running => Status::Running,
             Status::Success => Status::Failure,
             Status::Failure => Status::Success,
         }
     }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Inv"
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
 }
 
 impl<B: Bhv> Bhv for Pass<B> {
@@ -76,11 +97,23 @@ impl<B: Bhv> Bhv for Pass<B> {
     }
     #[inline]
     fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
-        match self.0.react(event, ctx) {
+        match record::react(0, &mut self.0, event, ctx) {
             Status::Running => Status::Running,
             _ => Status::Success,
         }
     }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Pass"
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
 }
 
 impl<B: Bhv> Bhv for Fail<B> {
@@ -91,11 +124,23 @@ impl<B: Bhv> Bhv for Fail<B> {
     }
     #[inline]
     fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
-        match self.0.react(event, ctx) {
+        match record::react(0, &mut self.0, event, ctx) {
             Status::Running => Status::Running,
             _ => Status::Failure,
         }
     }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Fail"
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
 }
 
 impl<B: Bhv> Bhv for Repeat<B> {
@@ -106,7 +151,7 @@ impl<B: Bhv> Bhv for Repeat<B> {
     }
     #[inline]
     fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
-        match self.bhv.react(event, ctx) {
+        match record::react(0, &mut self.bhv, event, ctx) {
             Status::Running => Status::Running,
             _ => {
                 self.current += 1;
@@ -114,6 +159,23 @@ impl<B: Bhv> Bhv for Repeat<B> {
             }
         }
     }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.current = 0;
+        self.bhv.halt(ctx)
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Repeat"
+    }
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Repeat({})", self.count))
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
 }
 
 impl<B: Bhv> Bhv for RepeatUntilPass<B> {
@@ -124,11 +186,23 @@ impl<B: Bhv> Bhv for RepeatUntilPass<B> {
     }
     #[inline]
     fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
-        match self.0.react(event, ctx) {
+        match record::react(0, &mut self.0, event, ctx) {
             Status::Success => Status::Success,
             _ => Status::Running,
         }
     }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "RepeatUntilPass"
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
 }
 
 impl<B: Bhv> Bhv for RepeatUntilFail<B> {
@@ -139,23 +213,75 @@ impl<B: Bhv> Bhv for RepeatUntilFail<B> {
     }
     #[inline]
     fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
-        match self.0.react(event, ctx) {
+        match record::react(0, &mut self.0, event, ctx) {
             Status::Failure => Status::Failure,
             _ => Status::Running,
         }
     }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "RepeatUntilFail"
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
 }
 
-impl<B: Bhv, E: EventType> Bhv for WaitFor<B, E> {
+impl<B: Bhv, E: Event> Bhv for WaitFor<B, E> {
     type Context = B::Context;
     #[inline]
     fn should_react_to(&self, kind: EventKind) -> bool {
         self.kind == kind
     }
     #[inline]
+    fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
+        record::react(0, &mut self.bhv, event, ctx)
+    }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.bhv.halt(ctx)
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "WaitFor"
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+}
+
+impl<B: Bhv> Bhv for Label<B> {
+    type Context = B::Context;
+    #[inline]
+    fn should_react_to(&self, kind: EventKind) -> bool {
+        self.bhv.should_react_to(kind)
+    }
+    #[inline]
     fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
         self.bhv.react(event, ctx)
     }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.bhv.halt(ctx)
+    }
+    #[inline]
+    fn kind(&self) -> &'static str {
+        self.bhv.kind()
+    }
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.bhv.visit_children(f)
+    }
 }
 
 impl<B: Bhv> Repeat<B> {
@@ -1,15 +1,126 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
+use std::any::{Any, TypeId};
 
-/// Type-safe value representing a unique ID for events. Defaults to the hash of the event type's
-/// name, but can be overwritten for cases like enum events where each variant is a separate type.
-#[derive(Eq, PartialEq, Debug, Copy, Clone)]
-pub struct EventKind(u64);
+/// Type-safe value representing a unique ID for events. Defaults to the [`TypeId`] of the event
+/// type, but can be overwritten through [`Event::event_kind`] for cases like enum events where each
+/// variant is a separate type.
+///
+/// Since [`TypeId`]s are not stable between builds, kinds can also be given an explicit numeric id
+/// through [`EventKind::stable`], for cases like serialized or recorded event streams.
+///
+/// Creating and comparing kinds takes constant time.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// struct Tick;
+/// impl EventType for Tick {}
+///
+/// struct Saved;
+/// impl EventType for Saved {
+///     const STABLE_ID: Option<u64> = Some(7);
+/// }
+///
+/// assert_eq!(Tick.event_type(), EventKind::of::<Tick>());
+/// assert_eq!(Tick.event_type().stable_id(), None);
+///
+/// assert_eq!(Saved.event_type(), EventKind::stable(7));
+/// assert_eq!(Saved.event_type().stable_id(), Some(7));
+/// ```
+#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone)]
+pub struct EventKind {
+    id: KindId,
+    variant: u32,
+}
+
+#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone)]
+enum KindId {
+    Type(TypeId),
+    Stable(u64),
+}
 
 /// A trait for types that are used as events to behavior tree nodes.
 /// For the sake of testing, the unit type () is considered an event type.
-pub trait Event {
+///
+/// For enums, `#[derive(Event)]` gives each variant its own name and [`EventKind`], so that nodes
+/// can wait for a single variant through [`BhvExt::wait_for_variant`](crate::BhvExt::wait_for_variant).
+/// Adding `#[event(id = N)]` bases the kinds on [`EventKind::stable_variant`] instead.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// #[derive(Event)]
+/// enum Input {
+///     Move(i32, i32),
+///     Jump,
+/// }
+///
+/// assert_eq!(Input::Jump.event_name(), "Input::Jump");
+/// assert_eq!(Input::Move(1, 2).event_type(), Input::Move(3, 4).event_type());
+/// assert_ne!(Input::Move(1, 2).event_type(), Input::Jump.event_type());
+///
+/// // kinds that stay the same between builds, such as for recorded events
+/// #[derive(Event)]
+/// #[event(id = 3)]
+/// enum Command {
+///     Stop,
+///     Go { speed: f32 },
+/// }
+///
+/// assert_eq!(Command::Stop.event_type(), EventKind::stable_variant(3, 0));
+/// assert_eq!(Command::Go { speed: 1.0 }.event_type(), EventKind::stable_variant(3, 1));
+/// ```
+pub trait Event: Any {
     /// A unique string describing the type of the event.
     fn event_name(&self) -> &str;
+
+    /// Check whether events of the given kind are of this type, such as any of the variants of an
+    /// enum. Used by nodes like [`on_event`](crate::on_event) to ignore events of other types.
+    /// Defaults to checking whether `kind` is based on the [`TypeId`] of this type.
+    #[inline]
+    fn matches_kind(kind: EventKind) -> bool
+        where Self: Sized {
+        kind.id == KindId::Type(TypeId::of::<Self>())
+    }
+
+    /// The kind of the event. Defaults to the kind of the type of the event, as given by
+    /// [`EventKind::of`], so enums implementing this trait by hand need to override it for their
+    /// variants to be told apart.
+    #[inline]
+    fn event_kind(&self) -> EventKind {
+        EventKind::of::<Self>()
+    }
+}
+
+impl dyn Event {
+    /// Get the event as [`Any`], so that it can be downcast to its concrete type.
+    #[inline]
+    pub fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    /// Get the event as a reference to `E`, if it is of type `E`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// struct Tick(u32);
+    /// impl EventType for Tick {}
+    ///
+    /// let event: &dyn Event = &Tick(3);
+    ///
+    /// assert_eq!(event.downcast_ref::<Tick>().map(|t| t.0), Some(3));
+    /// assert!(event.downcast_ref::<()>().is_none());
+    /// ```
+    #[inline]
+    pub fn downcast_ref<E: Event>(&self) -> Option<&E> {
+        self.as_any().downcast_ref()
+    }
 }
 
 pub trait EventExt {
@@ -19,15 +130,66 @@ pub trait EventExt {
 impl<E: Event + ?Sized> EventExt for E {
     #[inline]
     fn event_type(&self) -> EventKind {
-        let mut hasher = DefaultHasher::new();
-        self.event_name().hash(&mut hasher);
-        EventKind(hasher.finish())
+        self.event_kind()
+    }
+}
+
+impl EventKind {
+    /// The kind of the event type `E`.
+    #[inline]
+    pub fn of<E: ?Sized + 'static>() -> Self {
+        Self::variant::<E>(0)
+    }
+
+    /// The kind of the `index`-th variant of the enum event `E`.
+    #[inline]
+    pub fn variant<E: ?Sized + 'static>(index: u32) -> Self {
+        Self {
+            id: KindId::Type(TypeId::of::<E>()),
+            variant: index,
+        }
+    }
+
+    /// A kind identified by the given number, which stays the same between builds.
+    #[inline]
+    pub const fn stable(id: u64) -> Self {
+        Self::stable_variant(id, 0)
+    }
+
+    /// The kind of the `index`-th variant of the enum event identified by `id`, which stays the
+    /// same between builds.
+    #[inline]
+    pub const fn stable_variant(id: u64, index: u32) -> Self {
+        Self {
+            id: KindId::Stable(id),
+            variant: index,
+        }
+    }
+
+    /// The number identifying this kind, if it was created through [`EventKind::stable`] or
+    /// [`EventKind::stable_variant`].
+    #[inline]
+    pub fn stable_id(&self) -> Option<u64> {
+        match self.id {
+            KindId::Stable(id) => Some(id),
+            KindId::Type(_) => None,
+        }
+    }
+
+    /// The index of the variant this kind describes, or `0` for events that are not enums.
+    #[inline]
+    pub fn variant_index(&self) -> u32 {
+        self.variant
     }
 }
 
 /// A event type whose unique ID is not dependent on the event's value (ie. non-enum events).
 /// A type implementing [`EventType`] automatically implements [`Event`].
-pub trait EventType {}
+pub trait EventType: Any {
+    /// An explicit id of the type, used as its [`EventKind`] through [`EventKind::stable`].
+    /// Defaults to [`None`], in which case the kind of the type is [`EventKind::of`].
+    const STABLE_ID: Option<u64> = None;
+}
 
 pub trait EventTypeExt {
     /// A unique value describing the type of the event.
@@ -36,12 +198,7 @@ pub trait EventTypeExt {
         std::any::type_name::<Self>()
     }
 
-    #[inline]
-    fn static_event_type() -> EventKind {
-        let mut hasher = DefaultHasher::new();
-        std::any::type_name::<Self>().hash(&mut hasher);
-        EventKind(hasher.finish())
-    }
+    fn static_event_type() -> EventKind;
 }
 
 // just in case you don't want your own event type
@@ -59,11 +216,27 @@ impl Iterator for UnitEventPump {
     }
 }
 
-impl<E: EventType> EventTypeExt for E {}
+impl<E: EventType> EventTypeExt for E {
+    #[inline]
+    fn static_event_type() -> EventKind {
+        match E::STABLE_ID {
+            Some(id) => EventKind::stable(id),
+            None => EventKind::of::<E>(),
+        }
+    }
+}
 
 impl<E: EventType> Event for E {
     #[inline]
     fn event_name(&self) -> &str {
         E::static_event_name()
     }
+    #[inline]
+    fn event_kind(&self) -> EventKind {
+        E::static_event_type()
+    }
+    #[inline]
+    fn matches_kind(kind: EventKind) -> bool {
+        kind == E::static_event_type()
+    }
 }
\ No newline at end of file
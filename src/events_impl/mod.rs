@@ -5,10 +5,19 @@ pub use bhv_ext::BhvExt;
 pub use composite::*;
 pub use decor::*;
 pub use events::*;
+pub use bhv_derive::Event;
+pub use record::*;
+pub use runner::*;
+#[cfg(feature = "tracing")]
+pub use self::tracing::Traced;
 
 mod adapt;
 mod bhv_ext;
 mod composite;
 mod core;
 mod decor;
-mod events;
\ No newline at end of file
+mod events;
+mod record;
+mod runner;
+#[cfg(feature = "tracing")]
+mod tracing;
@@ -0,0 +1,436 @@
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::HashMap,
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::events_impl::{
+    core::{Bhv, Status},
+    events::{Event, EventKind},
+};
+use crate::introspect::NodeRef;
+
+/// An identifier of a node, unique within a recorded tree.
+///
+/// Nodes are numbered in pre-order as reported by [`Bhv::visit_children`], starting from 0
+/// for the root, so the identifiers stay the same between runs of the same tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeId(pub usize);
+
+/// A node that returned a status while reacting to a recorded event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Visit {
+    /// The identifier of the node.
+    pub id: NodeId,
+    /// The status returned by the node.
+    pub status: Status,
+}
+
+/// An event consumed by the root of a tree, together with the status returned in response.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TickTrace {
+    /// The [`Event::event_name`] of the consumed event.
+    pub event: String,
+    /// The status returned by the root.
+    pub status: Status,
+    /// The nodes that reacted to the event, in the order they returned. The root comes last.
+    pub visits: Vec<Visit>,
+}
+
+/// A recording of the execution of a tree, as created by a [`Recorded`] node.
+///
+/// Traces can be written to and read from files in a simple line-based text format. Under the
+/// `serde` feature, traces can also be (de)serialized with any `serde` format.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Trace {
+    pub ticks: Vec<TickTrace>,
+}
+
+/// A node that records every event its inner node reacts to, along with the status returned by
+/// each node of the tree. Created by [`BhvExt::recorded`](crate::BhvExt::recorded).
+pub struct Recorded<B> {
+    pub(crate) bhv: B,
+    pub(crate) trace: Trace,
+    pub(crate) ids: HashMap<Vec<usize>, usize>,
+}
+
+/// The visits of the tree that is currently being recorded, by path.
+struct Recording {
+    path: Vec<usize>,
+    visits: Vec<(Vec<usize>, Status)>,
+}
+
+thread_local! {
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+}
+
+/// The number of recorded trees reacting to an event on any thread. While this is zero, children
+/// react directly, without looking up the recording of the current thread.
+static RECORDED: AtomicUsize = AtomicUsize::new(0);
+
+/// Puts the given recording back as the one of the current thread when dropped, even if the code
+/// in between panics.
+struct Restore(Option<Recording>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let recording = self.0.take();
+        RECORDING.with(|r| *r.borrow_mut() = recording);
+        RECORDED.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The first difference between a [`Trace`] and the replayed execution of a tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// The index of the tick where the difference was found.
+    pub tick: usize,
+    /// The tick in the trace, if any.
+    pub expected: Option<TickTrace>,
+    /// The tick during the replay, if any.
+    pub actual: Option<TickTrace>,
+}
+
+impl Trace {
+    /// Write the trace in a line-based text format.
+    pub fn write_to(&self, mut w: impl Write) -> io::Result<()> {
+        writeln!(w, "bhv-trace 1")?;
+
+        for tick in &self.ticks {
+            writeln!(w, "tick {:?} {}", tick.status, tick.event)?;
+
+            for v in &tick.visits {
+                writeln!(w, "visit {} {:?}", v.id.0, v.status)?;
+            }
+        }
+
+        w.flush()
+    }
+
+    /// Read a trace written by [`Trace::write_to`].
+    pub fn read_from(r: impl BufRead) -> io::Result<Self> {
+        let mut lines = r.lines();
+
+        match lines.next().transpose()? {
+            Some(header) if header.trim() == "bhv-trace 1" => {}
+            _ => return Err(invalid_data("missing `bhv-trace 1` header")),
+        }
+
+        let mut trace = Trace::default();
+
+        for line in lines {
+            let line = line?;
+
+            match line.split_once(' ') {
+                None if line.trim().is_empty() => continue,
+                Some(("tick", rest)) => {
+                    let (status, event) = rest
+                        .split_once(' ')
+                        .ok_or_else(|| invalid_data(&format!("missing event name in `{}`", line)))?;
+
+                    trace.ticks.push(TickTrace {
+                        event: event.to_string(),
+                        status: parse_status(status, &line)?,
+                        visits: Vec::new(),
+                    });
+                }
+                Some(("visit", rest)) => {
+                    let (id, status) = rest
+                        .split_once(' ')
+                        .and_then(|(id, status)| Some((id.parse().ok()?, status)))
+                        .ok_or_else(|| invalid_data(&format!("bad node id in `{}`", line)))?;
+
+                    let visit = Visit {
+                        id: NodeId(id),
+                        status: parse_status(status, &line)?,
+                    };
+
+                    trace
+                        .ticks
+                        .last_mut()
+                        .ok_or_else(|| invalid_data("visit before the first tick"))?
+                        .visits
+                        .push(visit);
+                }
+                _ => return Err(invalid_data(&format!("unknown line `{}`", line))),
+            }
+        }
+
+        Ok(trace)
+    }
+
+    /// Write the trace to the file at `path`, replacing it if it exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Read the trace in the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+fn parse_status(status: &str, line: &str) -> io::Result<Status> {
+    match status.trim() {
+        "Running" => Ok(Status::Running),
+        "Success" => Ok(Status::Success),
+        "Failure" => Ok(Status::Failure),
+        _ => Err(invalid_data(&format!("bad status in `{}`", line))),
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Number the nodes of the tree in pre-order, by path.
+fn number(node: NodeRef<'_>, path: &mut Vec<usize>, ids: &mut HashMap<Vec<usize>, usize>) {
+    ids.insert(path.clone(), ids.len());
+
+    for (i, c) in node.children().into_iter().enumerate() {
+        path.push(i);
+        number(c, path, ids);
+        path.pop();
+    }
+}
+
+/// Let `bhv`, the child at `index` of the node being run, react to `event`, recording the returned
+/// status if a recorded tree is being run.
+#[inline]
+pub(crate) fn react<B: Bhv>(index: usize, bhv: &mut B, event: &dyn Event, ctx: &mut B::Context) -> Status {
+    if RECORDED.load(Ordering::Relaxed) == 0 {
+        return react_child(index, bhv, event, ctx);
+    }
+
+    let recording = RECORDING.with(|r| match r.borrow_mut().as_mut() {
+        Some(r) => {
+            r.path.push(index);
+            true
+        }
+        None => false,
+    });
+
+    let status = react_child(index, bhv, event, ctx);
+
+    if recording {
+        RECORDING.with(|r| {
+            if let Some(r) = r.borrow_mut().as_mut() {
+                let path = r.path.clone();
+                r.path.pop();
+                r.visits.push((path, status));
+            }
+        });
+    }
+
+    status
+}
+
+/// Let `bhv` react to `event`, inside of a span if a traced tree is being run.
+#[cfg(feature = "tracing")]
+#[inline]
+fn react_child<B: Bhv>(index: usize, bhv: &mut B, event: &dyn Event, ctx: &mut B::Context) -> Status {
+    crate::events_impl::tracing::react(index, bhv, event, ctx)
+}
+
+/// Let `bhv` react to `event`.
+#[cfg(not(feature = "tracing"))]
+#[inline]
+fn react_child<B: Bhv>(_index: usize, bhv: &mut B, event: &dyn Event, ctx: &mut B::Context) -> Status {
+    bhv.react(event, ctx)
+}
+
+impl<B: Bhv> Recorded<B> {
+    /// Record the events `bhv` reacts to.
+    #[inline]
+    pub(crate) fn new(bhv: B) -> Self {
+        let mut ids = HashMap::new();
+        number(NodeRef::new(&bhv), &mut Vec::new(), &mut ids);
+
+        Self {
+            bhv,
+            trace: Trace::default(),
+            ids,
+        }
+    }
+}
+
+impl<B> Recorded<B> {
+    /// The trace recorded so far.
+    #[inline]
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    /// Consume the node and return the recorded trace.
+    #[inline]
+    pub fn into_trace(self) -> Trace {
+        self.trace
+    }
+}
+
+impl<B: Bhv> Bhv for Recorded<B> {
+    type Context = B::Context;
+
+    #[inline]
+    fn should_react_to(&self, kind: EventKind) -> bool {
+        self.bhv.should_react_to(kind)
+    }
+
+    fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
+        RECORDED.fetch_add(1, Ordering::Relaxed);
+
+        let recording = Recording {
+            path: Vec::new(),
+            visits: Vec::new(),
+        };
+        let restore = Restore(RECORDING.with(|r| r.borrow_mut().replace(recording)));
+        let status = self.bhv.react(event, ctx);
+        let recording = RECORDING.with(|r| r.borrow_mut().take());
+        drop(restore);
+
+        let ids = &self.ids;
+        let mut visits: Vec<_> = recording
+            .into_iter()
+            .flat_map(|r| r.visits)
+            .filter_map(|(path, status)| Some(Visit { id: NodeId(*ids.get(&path)?), status }))
+            .collect();
+
+        visits.push(Visit {
+            id: NodeId(0),
+            status,
+        });
+
+        self.trace.ticks.push(TickTrace {
+            event: event.event_name().to_string(),
+            status,
+            visits,
+        });
+
+        status
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.bhv.halt(ctx)
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        self.bhv.kind()
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        self.bhv.name()
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.bhv.visit_children(f)
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tick {}: expected ", self.tick)?;
+
+        match &self.expected {
+            Some(t) => write!(f, "{}", TickDisplay(t))?,
+            None => write!(f, "no more events")?,
+        }
+
+        match &self.actual {
+            Some(t) => write!(f, ", but got {}", TickDisplay(t)),
+            None => write!(f, ", but the events ran out"),
+        }
+    }
+}
+
+struct TickDisplay<'a>(&'a TickTrace);
+
+impl fmt::Display for TickDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} in response to `{}` (visits:", self.0.status, self.0.event)?;
+
+        for v in &self.0.visits {
+            write!(f, " {} {:?}", v.id.0, v.status)?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+impl Error for Divergence {}
+
+/// Feed `events` to `bhv`, checking that the consumed events and the statuses returned by each node
+/// are the same as in `trace`. Returns the first difference found, if any.
+///
+/// One event is fed for each tick of the trace, even after the node completes, so a trace recorded
+/// across several runs of a tree can be replayed as is.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let mut tree = cond(|v: &i32| *v > 1).repeat_until_pass().recorded();
+///
+/// let mut ctx = 0;
+/// for e in UnitEventPump.take(3) {
+///     tree.react(e, &mut ctx);
+///     ctx += 1;
+/// }
+///
+/// let trace = tree.into_trace();
+/// assert_eq!(trace.ticks[0].status, Status::Running);
+///
+/// // the condition (node 1) fails, so the root (node 0) keeps running
+/// assert_eq!(trace.ticks[0].visits, [
+///     Visit { id: NodeId(1), status: Status::Failure },
+///     Visit { id: NodeId(0), status: Status::Running },
+/// ]);
+///
+/// // a tree that never succeeds diverges on the third event
+/// let other = cond(|_: &i32| false).repeat_until_pass();
+/// let err = replay(other, UnitEventPump, &mut 0, &trace).unwrap_err();
+/// assert_eq!(err.tick, 2);
+/// ```
+pub fn replay<'a, B: Bhv>(
+    bhv: B,
+    events: impl IntoIterator<Item=&'a dyn Event>,
+    ctx: &mut B::Context,
+    trace: &Trace,
+) -> Result<(), Divergence> {
+    let mut bhv = Recorded::new(bhv);
+    let mut events = events.into_iter();
+
+    for (tick, expected) in trace.ticks.iter().enumerate() {
+        let actual = events.next().map(|event| {
+            bhv.react(event, ctx);
+            bhv.trace.ticks.pop().expect("a tick is recorded for every event")
+        });
+
+        if actual.as_ref() != Some(expected) {
+            return Err(Divergence {
+                tick,
+                expected: Some(expected.clone()),
+                actual,
+            });
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::events_impl::{
+    core::{Bhv, Status},
+    events::{Event, EventExt},
+};
+
+/// A wrapper that owns a behavior tree and feeds it one event at a time, without consuming it.
+///
+/// Unlike [`BhvExt::execute`](crate::BhvExt::execute), which consumes events until the tree is
+/// done, [`Runner::tick`] lets the tree react to a single event, which makes it possible to store
+/// the tree inside of some other object (such as a game entity) and feed it the events of each
+/// frame. After the tree returns a status other than [`Status::Running`], it is halted so that the
+/// next event starts the tree over.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// struct Tick;
+///
+/// impl EventType for Tick {}
+///
+/// let mut runner = Runner::new(seq! {
+///     action(|v: &mut i32| *v += 1),
+///     cond(|v: &i32| *v % 3 == 0),
+/// }.wait_for::<Tick>());
+///
+/// let mut ctx = 0;
+///
+/// assert_eq!(runner.tick(&Tick, &mut ctx), Status::Failure); // ctx == 1
+/// assert_eq!(runner.tick(&Tick, &mut ctx), Status::Failure); // ctx == 2
+///
+/// // events the tree does not react to are skipped
+/// assert_eq!(runner.tick(&(), &mut ctx), Status::Running);
+///
+/// assert_eq!(runner.tick(&Tick, &mut ctx), Status::Success); // ctx == 3
+///
+/// assert_eq!(runner.ticks(), 3);
+/// assert_eq!(runner.last_status(), Some(Status::Success));
+/// ```
+pub struct Runner<B: Bhv> {
+    bhv: B,
+    ticks: u64,
+    last_status: Option<Status>,
+}
+
+impl<B: Bhv> Runner<B> {
+    /// Create a runner that owns the given tree.
+    #[inline]
+    pub fn new(bhv: B) -> Self {
+        Self {
+            bhv,
+            ticks: 0,
+            last_status: None,
+        }
+    }
+
+    /// Let the tree react to `event` once and return its status.
+    ///
+    /// If the tree does not react to events of this kind, the event is skipped and
+    /// [`Status::Running`] is returned. If the tree completes, it is halted before this function
+    /// returns.
+    pub fn tick(&mut self, event: &dyn Event, ctx: &mut B::Context) -> Status {
+        if !self.bhv.should_react_to(event.event_type()) {
+            return Status::Running;
+        }
+
+        let s = self.bhv.react(event, ctx);
+
+        if s != Status::Running {
+            self.bhv.halt(ctx);
+        }
+
+        self.ticks += 1;
+        self.last_status = Some(s);
+        s
+    }
+
+    /// Interrupt the tree if it is running, bringing it back to its initial state.
+    #[inline]
+    pub fn halt(&mut self, ctx: &mut B::Context) {
+        self.bhv.halt(ctx)
+    }
+
+    /// The number of events the tree has reacted to through [`Runner::tick`].
+    #[inline]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// The status returned by the last event the tree reacted to, if any.
+    #[inline]
+    pub fn last_status(&self) -> Option<Status> {
+        self.last_status
+    }
+
+    /// A reference to the tree owned by this runner.
+    #[inline]
+    pub fn bhv(&self) -> &B {
+        &self.bhv
+    }
+
+    /// A mutable reference to the tree owned by this runner.
+    #[inline]
+    pub fn bhv_mut(&mut self) -> &mut B {
+        &mut self.bhv
+    }
+
+    /// Consume the runner and return the tree it owns.
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.bhv
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::events_impl::{
+    core::{Bhv, Status},
+    events::{Event, EventKind},
+};
+use crate::introspect::NodeRef;
+
+/// A node that reports the events its inner node and the descendants of that node react to
+/// through the [`tracing`] crate. Created by [`BhvExt::traced`](crate::BhvExt::traced).
+///
+/// Each reaction of a node is wrapped in a span named `node`, with the fields `kind`, `name`,
+/// `path` and `event` describing the node and the event. Since span names have to be known at
+/// compile time, the kind of the node or the label given through
+/// [`BhvExt::label`](crate::BhvExt::label) is found in the `kind` and `name` fields respectively.
+/// The status returned by the node is emitted as an event inside of the span, while halting the
+/// tree is emitted as an event of its own. Everything is emitted at the `TRACE` level, with `bhv`
+/// as target.
+pub struct Traced<B>(pub(crate) B);
+
+thread_local! {
+    /// The path of the node reacting to an event in the tree being traced, if any.
+    static PATH: RefCell<Option<Vec<usize>>> = const { RefCell::new(None) };
+}
+
+/// The number of traced trees reacting to an event on any thread. While this is zero, children
+/// react directly, without looking up the path of the current thread.
+static TRACED: AtomicUsize = AtomicUsize::new(0);
+
+/// Puts the given path back as the one of the current thread when dropped, even if the code in
+/// between panics.
+struct Restore(Option<Vec<usize>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let path = self.0.take();
+        PATH.with(|p| *p.borrow_mut() = path);
+        TRACED.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Let `bhv`, the child at `index` of the node being run, react to `event`, inside of a span if a
+/// traced tree is being run.
+#[inline]
+pub(crate) fn react<B: Bhv>(index: usize, bhv: &mut B, event: &dyn Event, ctx: &mut B::Context) -> Status {
+    if TRACED.load(Ordering::Relaxed) == 0 {
+        return bhv.react(event, ctx);
+    }
+
+    let path = PATH.with(|p| {
+        p.borrow_mut().as_mut().map(|p| {
+            p.push(index);
+            p.clone()
+        })
+    });
+
+    match path {
+        Some(path) => {
+            let status = react_in_span(bhv, &path, event, ctx);
+            PATH.with(|p| {
+                if let Some(p) = p.borrow_mut().as_mut() {
+                    p.pop();
+                }
+            });
+            status
+        }
+        None => bhv.react(event, ctx),
+    }
+}
+
+fn react_in_span<B: Bhv>(bhv: &mut B, path: &[usize], event: &dyn Event, ctx: &mut B::Context) -> Status {
+    let span = tracing::trace_span!(
+        target: "bhv",
+        "node",
+        kind = bhv.kind(),
+        name = &*bhv.name(),
+        path = ?path,
+        event = event.event_name(),
+    );
+    let _entered = span.enter();
+
+    let status = bhv.react(event, ctx);
+    tracing::trace!(target: "bhv", ?status, "status");
+    status
+}
+
+impl<B: Bhv> Bhv for Traced<B> {
+    type Context = B::Context;
+
+    #[inline]
+    fn should_react_to(&self, kind: EventKind) -> bool {
+        self.0.should_react_to(kind)
+    }
+
+    fn react(&mut self, event: &dyn Event, ctx: &mut Self::Context) -> Status {
+        TRACED.fetch_add(1, Ordering::Relaxed);
+
+        let _restore = Restore(PATH.with(|p| p.borrow_mut().replace(Vec::new())));
+        react_in_span(&mut self.0, &[], event, ctx)
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        tracing::trace!(target: "bhv", kind = self.0.kind(), name = &*self.0.name(), "halt");
+        self.0.halt(ctx)
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        self.0.kind()
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        self.0.name()
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.0.visit_children(f)
+    }
+}
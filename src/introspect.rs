@@ -0,0 +1,222 @@
+use std::{borrow::Cow, fmt};
+
+use crate::Bhv;
+
+/// A reference to a node of a tree, used to inspect the structure of the tree regardless of the
+/// type of the node or its context.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let tree = sel! {
+///     seq! {
+///         cond(|v: &i32| *v > 0).label("is positive"),
+///         action(|v: &mut i32| *v -= 1),
+///     },
+///     action(|v: &mut i32| *v = 0).repeat(2),
+/// };
+///
+/// let root = NodeRef::new(&tree);
+/// assert_eq!(root.kind(), "Sel");
+///
+/// let names: Vec<_> = root.children()[0]
+///     .children()
+///     .iter()
+///     .map(|n| n.name().into_owned())
+///     .collect();
+///
+/// assert_eq!(names, ["is positive", "Action"]);
+/// assert_eq!(format!("{:?}", root), "Sel[Seq[is positive, Action], Repeat(2)[Action]]");
+/// ```
+#[derive(Clone, Copy)]
+pub struct NodeRef<'a>(Repr<'a>);
+
+/// A mutable reference to a node of a tree, used to restore the state of the tree regardless of
+/// the type of the node or its context. See [`crate::restore`].
+#[cfg(not(feature = "events"))]
+pub struct NodeMut<'a>(ReprMut<'a>);
+
+#[derive(Clone, Copy)]
+enum Repr<'a> {
+    Node(&'a dyn Inspect),
+    #[cfg(not(feature = "events"))]
+    Table(&'a dyn NodeTable, u32),
+}
+
+#[cfg(not(feature = "events"))]
+enum ReprMut<'a> {
+    Node(&'a mut dyn Inspect),
+    Table(&'a mut dyn NodeTable, u32),
+}
+
+/// The context-independent part of [`Bhv`], so that nodes of different contexts can be inspected
+/// the same way.
+trait Inspect {
+    fn kind(&self) -> &'static str;
+    fn name(&self) -> Cow<'_, str>;
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>));
+    #[cfg(not(feature = "events"))]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>));
+    #[cfg(not(feature = "events"))]
+    fn save_state(&self) -> Vec<u64>;
+    #[cfg(not(feature = "events"))]
+    fn load_state(&mut self, state: &[u64]) -> bool;
+}
+
+/// Nodes stored in a table and referred to by index, such as the nodes of a [`crate::FlatTree`],
+/// which can be inspected even though they are not values of their own. The methods are those of
+/// [`Inspect`], for the node at the given index.
+#[cfg(not(feature = "events"))]
+pub(crate) trait NodeTable {
+    fn kind(&self, node: u32) -> &'static str;
+    fn name(&self, node: u32) -> Cow<'_, str>;
+    fn visit_children<'a>(&'a self, node: u32, f: &mut dyn FnMut(NodeRef<'a>));
+    fn visit_children_mut(&mut self, node: u32, f: &mut dyn FnMut(NodeMut<'_>));
+    fn save_state(&self, node: u32) -> Vec<u64>;
+    fn load_state(&mut self, node: u32, state: &[u64]) -> bool;
+}
+
+impl<B: Bhv> Inspect for B {
+    #[inline]
+    fn kind(&self) -> &'static str {
+        Bhv::kind(self)
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Bhv::name(self)
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        Bhv::visit_children(self, f)
+    }
+
+    #[cfg(not(feature = "events"))]
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        Bhv::visit_children_mut(self, f)
+    }
+
+    #[cfg(not(feature = "events"))]
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        Bhv::save_state(self)
+    }
+
+    #[cfg(not(feature = "events"))]
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        Bhv::load_state(self, state)
+    }
+}
+
+impl<'a> NodeRef<'a> {
+    /// Create a reference to the given node.
+    #[inline]
+    pub fn new<B: Bhv>(bhv: &'a B) -> Self {
+        Self(Repr::Node(bhv))
+    }
+
+    /// Create a reference to the node at index `node` of `table`.
+    #[cfg(not(feature = "events"))]
+    #[inline]
+    pub(crate) fn in_table(table: &'a dyn NodeTable, node: u32) -> Self {
+        Self(Repr::Table(table, node))
+    }
+
+    /// The kind of the node. See [`Bhv::kind`].
+    #[inline]
+    pub fn kind(&self) -> &'static str {
+        match self.0 {
+            Repr::Node(n) => n.kind(),
+            #[cfg(not(feature = "events"))]
+            Repr::Table(t, i) => t.kind(i),
+        }
+    }
+
+    /// The name of the node. See [`Bhv::name`].
+    #[inline]
+    pub fn name(&self) -> Cow<'a, str> {
+        match self.0 {
+            Repr::Node(n) => n.name(),
+            #[cfg(not(feature = "events"))]
+            Repr::Table(t, i) => t.name(i),
+        }
+    }
+
+    /// Call `f` on each child of the node, in order. See [`Bhv::visit_children`].
+    #[inline]
+    pub fn visit_children(&self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        match self.0 {
+            Repr::Node(n) => n.visit_children(f),
+            #[cfg(not(feature = "events"))]
+            Repr::Table(t, i) => t.visit_children(i, f),
+        }
+    }
+
+    /// The runtime state of the node. See [`Bhv::save_state`].
+    #[cfg(not(feature = "events"))]
+    #[inline]
+    pub fn save_state(&self) -> Vec<u64> {
+        match self.0 {
+            Repr::Node(n) => n.save_state(),
+            Repr::Table(t, i) => t.save_state(i),
+        }
+    }
+
+    /// The children of the node, in order.
+    pub fn children(&self) -> Vec<NodeRef<'a>> {
+        let mut children = Vec::new();
+        self.visit_children(&mut |c| children.push(c));
+        children
+    }
+}
+
+#[cfg(not(feature = "events"))]
+impl<'a> NodeMut<'a> {
+    /// Create a mutable reference to the given node.
+    #[inline]
+    pub fn new<B: Bhv>(bhv: &'a mut B) -> Self {
+        Self(ReprMut::Node(bhv))
+    }
+
+    /// Create a mutable reference to the node at index `node` of `table`.
+    #[inline]
+    pub(crate) fn in_table(table: &'a mut dyn NodeTable, node: u32) -> Self {
+        Self(ReprMut::Table(table, node))
+    }
+
+    /// Call `f` on each child of the node, in order. See [`Bhv::visit_children_mut`].
+    #[inline]
+    pub fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        match &mut self.0 {
+            ReprMut::Node(n) => n.visit_children_mut(f),
+            ReprMut::Table(t, i) => t.visit_children_mut(*i, f),
+        }
+    }
+
+    /// Restore the state of the node. See [`Bhv::load_state`].
+    #[inline]
+    pub fn load_state(&mut self, state: &[u64]) -> bool {
+        match &mut self.0 {
+            ReprMut::Node(n) => n.load_state(state),
+            ReprMut::Table(t, i) => t.load_state(*i, state),
+        }
+    }
+}
+
+impl fmt::Debug for NodeRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())?;
+
+        let children = self.children();
+        if children.is_empty() {
+            Ok(())
+        } else {
+            f.debug_list().entries(children).finish()
+        }
+    }
+}
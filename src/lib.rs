@@ -1,5 +1,9 @@
 #![doc = include_str!("../README.md")]
 
+mod introspect;
+
+pub use introspect::*;
+
 #[cfg(feature = "events")]
 mod events_impl;
 
@@ -15,11 +15,12 @@ mod test_impl {
     impl EventType for Exit {}
 
     pub fn run() {
+        // the sequence fails on every `Tick`, as its last node only reacts to `Exit`
         let tree = seq! {
             action(|i| println!("i: {}", i)),
             action(|i| *i += 1),
             action(|_| println!("Exiting...")).wait_for::<Exit>()
-        };
+        }.repeat_until_pass();
 
         let event_queue: [&dyn Event; 6] = [
             &Tick(0),
@@ -35,6 +36,20 @@ mod test_impl {
         tree.execute(event_queue, &mut ctx);
 
         assert_eq!(ctx, 6);
+
+        // leaves can also read the data of the events they react to
+        let sum = async_on_event(|t: &Tick, sum: &mut u32| {
+            println!("tick: {}", t.0);
+            *sum += t.0;
+            Status::Running
+        });
+
+        let mut total = 0;
+
+        // the tree stops at the first event it does not react to
+        sum.execute(event_queue, &mut total);
+
+        assert_eq!(total, 10);
     }
 }
 
@@ -1,4 +1,10 @@
-use std::marker::PhantomData;
+use std::{
+    borrow::Cow,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{self, Poll, Waker},
+};
 
 use crate::{Bhv, Status};
 
@@ -19,6 +25,22 @@ pub struct Action<Ctx, A>(A, PhantomData<Ctx>)
 pub struct AsyncAction<Ctx, A>(A, PhantomData<Ctx>)
     where A: FnMut(&mut Ctx) -> Status;
 
+/// The type of the result of [`future_action`].
+pub struct FutureAction<Ctx, F, Fut> {
+    make: F,
+    future: Option<Pin<Box<Fut>>>,
+    waker: Waker,
+    _tag: PhantomData<Ctx>,
+}
+
+/// The type of the result of [`wait_ticks`].
+#[derive(Clone)]
+pub struct WaitTicks<Ctx> {
+    count: u32,
+    current: u32,
+    _tag: PhantomData<Ctx>,
+}
+
 /// Adapt a predicate into a behavior, returning [`Status::Success`] if
 /// the predicate returns `true` and [`Status::Failure`] otherwise.
 ///
@@ -102,12 +124,120 @@ pub fn action<Ctx, A>(a: A) -> Action<Ctx, A>
 pub fn async_action<Ctx, A>(a: A) -> AsyncAction<Ctx, A>
     where A: FnMut(&mut Ctx) -> Status { AsyncAction(a, PhantomData) }
 
+/// Wrap a function creating a [`Future`] into a behavior, so that long-running leaves can be
+/// written as `async` code.
+///
+/// The future is created by calling `f` when the node starts, then polled once on every update,
+/// returning [`Status::Running`] while it is pending. Once the future completes, `Ok` results in
+/// [`Status::Success`] and `Err` results in [`Status::Failure`]. The future is dropped when the
+/// node is reset or halted, so the next update starts a new one.
+///
+/// No async runtime is needed, as the future is polled by the tree itself with a waker that does
+/// nothing. Use [`FutureAction::with_waker`] to be notified when the future can make progress.
+///
+/// Since the context is only borrowed while `f` runs, the future cannot hold on to it; state that
+/// is shared between the future and the rest of the tree has to be cloned into the future.
+///
+/// A pending future cannot be saved, so a [`crate::snapshot`] taken while the future is pending
+/// cannot be restored: [`crate::restore`] fails with [`crate::RestoreError::InvalidState`] instead.
+///
+/// # Example
+///
+/// ```
+/// use std::{cell::Cell, future::poll_fn, rc::Rc, task::Poll};
+///
+/// use bhv::*;
+///
+/// let reply = Rc::new(Cell::new(None));
+/// let inbox = reply.clone();
+///
+/// let ask = future_action(move |_: &mut ()| {
+///     let inbox = inbox.clone();
+///
+///     async move {
+///         // wait for the reply without blocking the tree
+///         let answer = poll_fn(|_| match inbox.take() {
+///             Some(answer) => Poll::Ready(answer),
+///             None => Poll::Pending,
+///         }).await;
+///
+///         if answer == 42 { Ok(()) } else { Err("wrong answer") }
+///     }
+/// });
+///
+/// let mut runner = Runner::new(ask);
+///
+/// assert_eq!(runner.tick(&mut ()), Status::Running);
+/// assert_eq!(runner.tick(&mut ()), Status::Running);
+///
+/// reply.set(Some(42));
+/// assert_eq!(runner.tick(&mut ()), Status::Success);
+///
+/// // a snapshot of a pending future cannot be restored
+/// let mut pending = future_action(|_: &mut ()| std::future::pending::<Result<(), ()>>());
+/// pending.update(&mut ());
+///
+/// let saved = snapshot(&pending);
+/// let mut fresh = future_action(|_: &mut ()| std::future::pending::<Result<(), ()>>());
+///
+/// assert!(matches!(restore(&mut fresh, &saved), Err(RestoreError::InvalidState { .. })));
+/// ```
+#[inline]
+pub fn future_action<Ctx, F, Fut, T, E>(f: F) -> FutureAction<Ctx, F, Fut>
+    where
+        F: FnMut(&mut Ctx) -> Fut,
+        Fut: Future<Output=Result<T, E>>,
+{
+    FutureAction {
+        make: f,
+        future: None,
+        waker: Waker::noop().clone(),
+        _tag: PhantomData,
+    }
+}
+
+impl<Ctx, F, Fut> FutureAction<Ctx, F, Fut> {
+    /// Poll the future with `waker` instead of a waker that does nothing, for example to wake up
+    /// the thread that runs the tree.
+    #[inline]
+    pub fn with_waker(self, waker: Waker) -> Self {
+        Self { waker, ..self }
+    }
+}
+
+/// Create a behavior that returns [`Status::Running`] for the given number of updates,
+/// then returns [`Status::Success`].
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let mut runner = Runner::new(wait_ticks(2));
+///
+/// assert_eq!(runner.tick(&mut ()), Status::Running);
+/// assert_eq!(runner.tick(&mut ()), Status::Running);
+/// assert_eq!(runner.tick(&mut ()), Status::Success);
+/// ```
+#[inline]
+pub fn wait_ticks<Ctx>(count: u32) -> WaitTicks<Ctx> {
+    WaitTicks {
+        count,
+        current: 0,
+        _tag: PhantomData,
+    }
+}
+
 impl<Ctx, C> Bhv for Cond<Ctx, C>
     where
         C: Fn(&Ctx) -> bool,
 {
     type Context = Ctx;
     #[inline]
+    fn kind(&self) -> &'static str {
+        "Cond"
+    }
+    #[inline]
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
         if self.0(ctx) {
             Status::Success
@@ -123,6 +253,10 @@ impl<Ctx, A> Bhv for Action<Ctx, A>
 {
     type Context = Ctx;
     #[inline]
+    fn kind(&self) -> &'static str {
+        "Action"
+    }
+    #[inline]
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
         self.0(ctx);
         Status::Success
@@ -134,7 +268,115 @@ impl<Ctx, A> Bhv for AsyncAction<Ctx, A>
 {
     type Context = Ctx;
     #[inline]
+    fn kind(&self) -> &'static str {
+        "AsyncAction"
+    }
+    #[inline]
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
         self.0(ctx)
     }
 }
+
+impl<Ctx, F, Fut, T, E> Bhv for FutureAction<Ctx, F, Fut>
+    where
+        F: FnMut(&mut Ctx) -> Fut,
+        Fut: Future<Output=Result<T, E>>,
+{
+    type Context = Ctx;
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "FutureAction"
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        let future = self.future.get_or_insert_with(|| Box::pin((self.make)(ctx)));
+
+        match future.as_mut().poll(&mut task::Context::from_waker(&self.waker)) {
+            Poll::Pending => Status::Running,
+            Poll::Ready(r) => {
+                self.future = None;
+
+                match r {
+                    Ok(_) => Status::Success,
+                    Err(_) => Status::Failure,
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn reset(&mut self, _status: Status) {
+        self.future = None;
+    }
+
+    #[inline]
+    fn halt(&mut self, _ctx: &mut Self::Context) {
+        self.future = None;
+    }
+
+    /// The state is whether the future is pending.
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.future.is_some() as u64]
+    }
+
+    /// Only a node without a pending future can be restored, as the future itself is not saved.
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match state {
+            [0] => {
+                self.future = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<Ctx> Bhv for WaitTicks<Ctx> {
+    type Context = Ctx;
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "WaitTicks"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("WaitTicks({})", self.count))
+    }
+
+    #[inline]
+    fn update(&mut self, _ctx: &mut Self::Context) -> Status {
+        if self.current >= self.count {
+            self.current = 0;
+            Status::Success
+        } else {
+            self.current += 1;
+            Status::Running
+        }
+    }
+
+    #[inline]
+    fn reset(&mut self, _status: Status) {
+        self.current = 0;
+    }
+
+    #[inline]
+    fn halt(&mut self, _ctx: &mut Self::Context) {
+        self.current = 0;
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.current as u64]
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match *state {
+            [current] if current <= self.count as u64 => {
+                self.current = current as u32;
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,47 +1,128 @@
-use crate::old_impl::core::{Bhv, Status};
+use std::{borrow::Cow, fmt};
+
+use crate::old_impl::{
+    core::{Bhv, Status},
+    observe,
+    snapshot,
+};
+use crate::introspect::{NodeMut, NodeRef};
 
 /// A node that runs its child nodes in turn until one of them completes successfully (if any), or
 /// all of the children complete otherwise. If all the children return [`Status::Failure`],
 /// then this node also returns [`Status::Failure`], otherwise it returns [`Status::Success`] after
-/// the first child node that succeeds. If there are no failures but there are still nodes that are
-/// running, this returns [`Status::Running`]. This node is similar to [`Sel`], with the difference
+/// the first child node that succeeds, halting the children that are still running. If there are no
+/// successes but there are still nodes that are running, this returns [`Status::Running`]. This node is similar to [`Sel`], with the difference
 /// being that this node runs its children even after one of them returns [`Status::Running`],
 /// instead of waiting for the next call of [`Bhv::update`].
-pub struct WhenAny<C>(pub(crate) Box<[Box<dyn Bhv<Context=C>>]>);
+pub struct WhenAny<C> {
+    pub(crate) nodes: Box<[Box<dyn Bhv<Context=C>>]>,
+    pub(crate) running: Box<[bool]>,
+}
 
 /// A node that runs its child nodes in turn until all of them complete. If all the children return
 /// [`Status::Success`], then this node also returns [`Status::Success`], otherwise it returns
-/// [`Status::Failure`] after the first child node that fails. If there are no failures but there
+/// [`Status::Failure`] after the first child node that fails, halting the children that are still
+/// running. If there are no failures but there
 /// is at least one node that returns [`Status::Running`], all the nodes are run and
 /// [`Status::Running`] is returned. This node is similar to [`Seq`], with the difference
 /// being that this node runs its children even after one of them returns [`Status::Running`],
 /// instead of waiting for the next call of [`Bhv::update`].
-pub struct WhenAll<C>(pub(crate) Box<[Box<dyn Bhv<Context=C>>]>);
+pub struct WhenAll<C> {
+    pub(crate) nodes: Box<[Box<dyn Bhv<Context=C>>]>,
+    pub(crate) running: Box<[bool]>,
+}
+
+/// A node that runs its child nodes in turn until enough of them complete. If at least `success`
+/// children return [`Status::Success`], then this node also returns [`Status::Success`]. If at least
+/// `failure` children return [`Status::Failure`], or there are not enough children left for the node
+/// to succeed, then this node returns [`Status::Failure`]. Otherwise, [`Status::Running`] is returned.
+/// Unlike [`WhenAny`] and [`WhenAll`], the children that complete are not run again until this node
+/// completes, in which case the children that are still running are halted and all the children are
+/// reset.
+pub struct Parallel<C> {
+    pub(crate) nodes: Box<[Box<dyn Bhv<Context=C>>]>,
+    pub(crate) statuses: Box<[Option<Status>]>,
+    pub(crate) success: usize,
+    pub(crate) failure: usize,
+}
 
 impl<C> WhenAny<C> {
     #[inline]
     pub fn new(bhvs: Box<[Box<dyn Bhv<Context=C>>]>) -> Self {
-        Self(bhvs)
+        Self {
+            running: vec![false; bhvs.len()].into_boxed_slice(),
+            nodes: bhvs,
+        }
     }
 }
 
 impl<C> WhenAll<C> {
     #[inline]
     pub fn new(bhvs: Box<[Box<dyn Bhv<Context=C>>]>) -> Self {
-        Self(bhvs)
+        Self {
+            running: vec![false; bhvs.len()].into_boxed_slice(),
+            nodes: bhvs,
+        }
+    }
+}
+
+/// Halt the nodes that are marked as running and clear their flags.
+fn halt_running<C>(nodes: &mut [Box<dyn Bhv<Context=C>>], running: &mut [bool], ctx: &mut C) {
+    for (i, (node, running)) in nodes.iter_mut().zip(running.iter_mut()).enumerate() {
+        if *running {
+            observe::halt(i, node, ctx);
+            *running = false;
+        }
     }
 }
 
+/// Save the flags of the running nodes as the state of a snapshot.
+fn save_running(running: &[bool]) -> Vec<u64> {
+    running.iter().map(|r| *r as u64).collect()
+}
+
+/// Restore the flags of the running nodes from the state of a snapshot.
+fn load_running(running: &mut [bool], state: &[u64]) -> bool {
+    if state.len() != running.len() || state.iter().any(|v| *v > 1) {
+        return false;
+    }
+
+    running.iter_mut().zip(state).for_each(|(r, v)| *r = *v == 1);
+    true
+}
+
 impl<C> Bhv for WhenAny<C> {
     type Context = C;
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "WhenAny"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.nodes.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.nodes.iter_mut().for_each(|n| f(NodeMut::new(n)))
+    }
+
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
         let mut any_running = false;
 
-        for node in self.0.iter_mut() {
-            match node.update(ctx) {
+        for (i, node) in self.nodes.iter_mut().enumerate() {
+            let s = observe::update(i, node, ctx);
+            self.running[i] = s == Status::Running;
+
+            match s {
                 Status::Running => any_running = true,
                 Status::Failure => continue,
-                Status::Success => return Status::Success,
+                Status::Success => {
+                    // the other nodes are not needed anymore
+                    halt_running(&mut self.nodes, &mut self.running, ctx);
+                    return Status::Success;
+                }
             }
         }
 
@@ -51,18 +132,55 @@ impl<C> Bhv for WhenAny<C> {
             Status::Failure
         }
     }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        halt_running(&mut self.nodes, &mut self.running, ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        save_running(&self.running)
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        load_running(&mut self.running, state)
+    }
 }
 
 impl<C> Bhv for WhenAll<C> {
     type Context = C;
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "WhenAll"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.nodes.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.nodes.iter_mut().for_each(|n| f(NodeMut::new(n)))
+    }
+
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
         let mut any_running = false;
 
-        for node in self.0.iter_mut() {
-            match node.update(ctx) {
+        for (i, node) in self.nodes.iter_mut().enumerate() {
+            let s = observe::update(i, node, ctx);
+            self.running[i] = s == Status::Running;
+
+            match s {
                 Status::Running => any_running = true,
                 Status::Success => continue,
-                Status::Failure => return Status::Failure,
+                Status::Failure => {
+                    // the other nodes cannot make this node succeed anymore
+                    halt_running(&mut self.nodes, &mut self.running, ctx);
+                    return Status::Failure;
+                }
             }
         }
 
@@ -72,6 +190,154 @@ impl<C> Bhv for WhenAll<C> {
             Status::Success
         }
     }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        halt_running(&mut self.nodes, &mut self.running, ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        save_running(&self.running)
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        load_running(&mut self.running, state)
+    }
+}
+
+impl<C> Parallel<C> {
+    /// Create a node that succeeds when `success` children succeed and fails when `failure`
+    /// children fail.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `success` or `failure` is zero or greater than the number of children.
+    #[inline]
+    pub fn new(bhvs: Box<[Box<dyn Bhv<Context=C>>]>, success: usize, failure: usize) -> Self {
+        assert!(
+            (1..=bhvs.len()).contains(&success),
+            "the success threshold should be between 1 and the number of children",
+        );
+        assert!(
+            (1..=bhvs.len()).contains(&failure),
+            "the failure threshold should be between 1 and the number of children",
+        );
+
+        Self {
+            statuses: vec![None; bhvs.len()].into_boxed_slice(),
+            nodes: bhvs,
+            success,
+            failure,
+        }
+    }
+
+    /// Check whether the outcome of the node is decided based on the children that completed.
+    fn outcome(&self) -> Option<Status> {
+        let successes = self.statuses.iter().filter(|s| **s == Some(Status::Success)).count();
+        let failures = self.statuses.iter().filter(|s| **s == Some(Status::Failure)).count();
+
+        if successes >= self.success {
+            Some(Status::Success)
+        } else if failures >= self.failure || self.nodes.len() - failures < self.success {
+            Some(Status::Failure)
+        } else {
+            None
+        }
+    }
+}
+
+impl<C> Bhv for Parallel<C> {
+    type Context = C;
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Parallel"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Parallel(success = {}, failure = {})", self.success, self.failure))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.nodes.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.nodes.iter_mut().for_each(|n| f(NodeMut::new(n)))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        for i in 0..self.nodes.len() {
+            if matches!(self.statuses[i], Some(Status::Success | Status::Failure)) {
+                continue;
+            }
+
+            self.statuses[i] = Some(observe::update(i, &mut self.nodes[i], ctx));
+
+            if let Some(s) = self.outcome() {
+                self.halt(ctx);
+                return s;
+            }
+        }
+
+        match self.outcome() {
+            Some(s) => {
+                self.halt(ctx);
+                s
+            }
+            None => Status::Running,
+        }
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        for (i, (node, status)) in self.nodes.iter_mut().zip(self.statuses.iter_mut()).enumerate() {
+            match status.take() {
+                Some(Status::Running) => observe::halt(i, node, ctx),
+                Some(s) => observe::reset(i, node, s),
+                None => {}
+            }
+        }
+    }
+
+    fn save_state(&self) -> Vec<u64> {
+        self.statuses.iter().map(|s| snapshot::encode_status(*s)).collect()
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        if state.len() != self.statuses.len() {
+            return false;
+        }
+
+        match state.iter().map(|v| snapshot::decode_status(*v)).collect::<Option<Vec<_>>>() {
+            Some(statuses) => {
+                self.statuses.copy_from_slice(&statuses);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<C> fmt::Debug for WhenAny<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<C> fmt::Debug for WhenAll<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<C> fmt::Debug for Parallel<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
 }
 
 /// A macro used to create an [`WhenAny`] from a list of behaviors.
@@ -133,4 +399,59 @@ macro_rules! when_all {
             Box::new([$(Box::new($x)),+]),
         )
     };
-}
\ No newline at end of file
+}
+
+/// A macro used to create a [`Parallel`] from the success and failure thresholds, followed by a
+/// list of behaviors.
+///
+/// # Panics
+///
+/// Panics if either threshold is zero or greater than the number of behaviors. See [`Parallel::new`].
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// #[derive(Default)]
+/// struct Squad {
+///     reports: u32,
+///     lost: u32,
+/// }
+///
+/// let tree = parallel! {
+///     success = 2, failure = 2;
+///     action(|s: &mut Squad| s.reports += 1), // first scout reports back
+///     action(|s: &mut Squad| s.lost += 1).fail(), // second scout is lost
+///     action(|s: &mut Squad| s.reports += 1), // third scout reports back
+/// };
+///
+/// let mut squad = Squad::default();
+/// assert!(tree.execute(&mut squad) == true);
+/// assert_eq!(squad.reports, 2);
+/// assert_eq!(squad.lost, 1);
+/// ```
+///
+/// ```should_panic
+/// use bhv::*;
+///
+/// // two children can never reach three successes
+/// let tree = parallel! {
+///     success = 3, failure = 1;
+///     action(|_: &mut ()| {}),
+///     action(|_: &mut ()| {}),
+/// };
+/// ```
+#[macro_export]
+macro_rules! parallel {
+    (success = $s:expr, failure = $f:expr$(;)?) => {
+        compile_error!("`parallel` should have at least one behavior!")
+    };
+    (success = $s:expr, failure = $f:expr; $($x:expr),+$(,)?) => {
+        $crate::Parallel::new(
+            Box::new([$(Box::new($x)),+]),
+            $s,
+            $f,
+        )
+    };
+}
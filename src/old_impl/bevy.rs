@@ -0,0 +1,262 @@
+use bevy_ecs::{
+    component::Mutable,
+    prelude::{Component, Entity, Resource, With, World},
+};
+use bevy_platform::cell::SyncCell;
+
+use crate::old_impl::{
+    adapt::{async_action, cond, AsyncAction, Cond},
+    core::{Bhv, Status},
+    instance::TreeDef,
+    runner::Runner,
+};
+
+/// A tree stored in a [`BehaviorTree`], which runs on the whole [`World`].
+pub type EcsBhv = Box<dyn Bhv<Context=World> + Send>;
+
+/// A component holding the behavior tree of an entity.
+///
+/// Trees stored in this component are run once per call of [`tick_behavior_trees`], with the
+/// [`World`] as context and the entity owning the tree available through the [`CurrentEntity`]
+/// resource. The tree is only ever accessed through a mutable reference, so its nodes only have to
+/// be `Send`. They can be composed through [`crate::seq_t!`], [`crate::sel_t!`] and the decorators
+/// of [`crate::BhvExt`], or come from a shared [`TreeDef`] through [`BehaviorTree::from_def`].
+///
+/// # Example
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+/// use bhv::*;
+///
+/// #[derive(Component)]
+/// struct Health(u32);
+///
+/// let mut world = World::new();
+/// let mut schedule = Schedule::default();
+/// schedule.add_systems(tick_behavior_trees);
+///
+/// let heal = seq_t! {
+///     component_cond(|h: &Health| h.0 < 10),
+///     component_action(|h: &mut Health| h.0 += 1),
+/// };
+///
+/// let npc = world.spawn((Health(8), BehaviorTree::new(heal.repeat_until_fail()))).id();
+///
+/// for _frame in 0..5 {
+///     schedule.run(&mut world);
+/// }
+///
+/// assert_eq!(world.get::<Health>(npc).unwrap().0, 10);
+/// assert_eq!(world.get::<BehaviorTree>(npc).unwrap().last_status(), Some(Status::Failure));
+/// ```
+#[derive(Component)]
+pub struct BehaviorTree {
+    runner: Option<SyncCell<Runner<EcsBhv>>>,
+    ticks: u64,
+    last_status: Option<Status>,
+}
+
+/// A resource holding the entity whose tree is being run by [`tick_behavior_trees`].
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CurrentEntity(pub Entity);
+
+impl BehaviorTree {
+    /// Create a component holding the given tree.
+    #[inline]
+    pub fn new(bhv: impl Bhv<Context=World> + Send + 'static) -> Self {
+        Self {
+            runner: Some(SyncCell::new(Runner::new(Box::new(bhv)))),
+            ticks: 0,
+            last_status: None,
+        }
+    }
+
+    /// Create a component holding a new instance of the given tree definition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bevy_ecs::prelude::*;
+    /// use bhv::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Health(u32);
+    ///
+    /// let mut b = TreeDef::builder();
+    /// let hurt = b.leaf(|| component_cond(|h: &Health| h.0 < 10));
+    /// let heal = b.leaf(|| component_action(|h: &mut Health| h.0 += 1));
+    /// let root = b.seq([hurt, heal]);
+    /// let def = b.build(root);
+    ///
+    /// let mut world = World::new();
+    /// let npcs: Vec<_> = (0..3)
+    ///     .map(|i| world.spawn((Health(7 + i), BehaviorTree::from_def(&def))).id())
+    ///     .collect();
+    ///
+    /// tick_behavior_trees(&mut world);
+    ///
+    /// let health: Vec<_> = npcs.iter().map(|&e| world.get::<Health>(e).unwrap().0).collect();
+    /// assert_eq!(health, [8, 9, 10]);
+    /// ```
+    #[inline]
+    pub fn from_def(def: &TreeDef<World>) -> Self {
+        Self::new(def.instantiate())
+    }
+
+    /// The number of times the tree has been run.
+    #[inline]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// The status returned by the last run of the tree, if any.
+    #[inline]
+    pub fn last_status(&self) -> Option<Status> {
+        self.last_status
+    }
+}
+
+/// An exclusive system running the tree of every entity with a [`BehaviorTree`] once.
+///
+/// While a tree is running, its entity is stored in the [`CurrentEntity`] resource, which is
+/// removed once every tree has run. The tree is taken out of its component while running, so a
+/// tree can despawn its own entity or replace its own component.
+pub fn tick_behavior_trees(world: &mut World) {
+    let entities: Vec<_> = world
+        .query_filtered::<Entity, With<BehaviorTree>>()
+        .iter(world)
+        .collect();
+
+    for entity in entities {
+        let runner = world
+            .get_mut::<BehaviorTree>(entity)
+            .and_then(|mut tree| tree.runner.take());
+
+        let Some(mut runner) = runner else {
+            continue;
+        };
+
+        world.insert_resource(CurrentEntity(entity));
+        runner.get().tick(world);
+
+        // the tree could have removed or replaced its own component
+        if let Some(mut tree) = world.get_mut::<BehaviorTree>(entity) {
+            if tree.runner.is_none() {
+                let r = runner.get();
+                tree.ticks = r.ticks();
+                tree.last_status = r.last_status();
+                tree.runner = Some(runner);
+            }
+        }
+    }
+
+    world.remove_resource::<CurrentEntity>();
+}
+
+/// Adapt a predicate on a component of the [`CurrentEntity`] into a behavior, returning
+/// [`Status::Success`] if the entity has the component and the predicate returns `true`, and
+/// [`Status::Failure`] otherwise.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+/// use bhv::*;
+///
+/// #[derive(Component)]
+/// struct Ammo(u32);
+///
+/// let mut world = World::new();
+/// let npc = world.spawn(Ammo(0)).id();
+/// world.insert_resource(CurrentEntity(npc));
+///
+/// assert_eq!(component_cond(|a: &Ammo| a.0 > 0).execute(&mut world), false);
+/// ```
+#[inline]
+pub fn component_cond<T, P>(pred: P) -> Cond<World, impl Fn(&World) -> bool>
+    where
+        T: Component,
+        P: Fn(&T) -> bool,
+{
+    cond(move |world: &World| {
+        world
+            .get_resource::<CurrentEntity>()
+            .and_then(|e| world.get::<T>(e.0))
+            .is_some_and(&pred)
+    })
+}
+
+/// Adapt a function modifying a component of the [`CurrentEntity`] into a behavior, returning
+/// [`Status::Success`] if the entity has the component and [`Status::Failure`] otherwise.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+/// use bhv::*;
+///
+/// #[derive(Component)]
+/// struct Ammo(u32);
+///
+/// let mut world = World::new();
+/// let npc = world.spawn(Ammo(0)).id();
+/// world.insert_resource(CurrentEntity(npc));
+///
+/// assert!(component_action(|a: &mut Ammo| a.0 += 5).execute(&mut world));
+/// assert_eq!(world.get::<Ammo>(npc).unwrap().0, 5);
+/// ```
+#[inline]
+pub fn component_action<T, A>(mut a: A) -> AsyncAction<World, impl FnMut(&mut World) -> Status>
+    where
+        T: Component<Mutability=Mutable>,
+        A: FnMut(&mut T),
+{
+    component_async_action(move |c: &mut T| {
+        a(c);
+        Status::Success
+    })
+}
+
+/// Adapt a function modifying a component of the [`CurrentEntity`] and returning a [`Status`]
+/// into a behavior. The behavior returns [`Status::Failure`] if the entity does not have the
+/// component.
+///
+/// # Example
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+/// use bhv::*;
+///
+/// #[derive(Component)]
+/// struct Position(i32);
+///
+/// let mut world = World::new();
+/// let npc = world.spawn(Position(0)).id();
+/// world.insert_resource(CurrentEntity(npc));
+///
+/// let walk = component_async_action(|p: &mut Position| {
+///     p.0 += 1;
+///     if p.0 < 3 { Status::Running } else { Status::Success }
+/// });
+///
+/// assert!(walk.execute(&mut world));
+/// assert_eq!(world.get::<Position>(npc).unwrap().0, 3);
+/// ```
+#[inline]
+pub fn component_async_action<T, A>(mut a: A) -> AsyncAction<World, impl FnMut(&mut World) -> Status>
+    where
+        T: Component<Mutability=Mutable>,
+        A: FnMut(&mut T) -> Status,
+{
+    async_action(move |world: &mut World| {
+        let entity = match world.get_resource::<CurrentEntity>() {
+            Some(e) => e.0,
+            None => return Status::Failure,
+        };
+
+        match world.get_mut::<T>(entity) {
+            Some(mut c) => a(&mut c),
+            None => Status::Failure,
+        }
+    })
+}
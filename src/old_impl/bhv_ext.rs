@@ -1,7 +1,13 @@
+use std::{borrow::Cow, marker::PhantomData, time::Duration};
+
 #[allow(unused_imports)]
 use crate::old_impl::{
+    blackboard::{Blackboard, Scoped},
     core::{Bhv, Status},
     decor::*,
+    observe::{Observed, TreeObserver},
+    retry::{Attempts, Backoff, Retry, RetryWithBackoff},
+    time::{Cooldown, Delay, SystemClock, Throttle, Timeout},
 };
 
 /// Helper methods to build a tree from given nodes.
@@ -88,6 +94,7 @@ pub trait BhvExt: Bhv + Sized {
         RunIf {
             bhv: self,
             cond,
+            running: false,
         }
     }
 
@@ -192,6 +199,469 @@ pub trait BhvExt: Bhv + Sized {
     fn repeat_until_fail(self) -> RepeatUntilFail<Self> {
         RepeatUntilFail(self)
     }
+
+    /// Return a node that runs this node again when it fails, until it fails `attempts` times.
+    /// The number of failed attempts can be read through [`Retry::attempts`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// // a gripper that only manages to grab on the third try
+    /// let grab = async_action(|tries: &mut u32| {
+    ///     *tries += 1;
+    ///     if *tries == 3 { Status::Success } else { Status::Failure }
+    /// });
+    ///
+    /// let mut tries = 0;
+    /// assert_eq!(grab.clone().retry(5).execute(&mut tries), true);
+    /// assert_eq!(tries, 3);
+    ///
+    /// let mut tries = 0;
+    /// assert_eq!(grab.retry(2).execute(&mut tries), false);
+    /// assert_eq!(tries, 2);
+    ///
+    /// // the counter can be read by other nodes of the tree
+    /// let grab = async_action(|_: &mut ()| Status::Failure).retry(3);
+    /// let attempts = grab.attempts();
+    ///
+    /// let tree = sel! {
+    ///     grab,
+    ///     cond(move |_| attempts.get() == 3),
+    /// };
+    ///
+    /// assert_eq!(tree.execute(&mut ()), true);
+    /// ```
+    #[inline]
+    fn retry(self, attempts: u32) -> Retry<Self> {
+        Retry {
+            bhv: self,
+            max: attempts,
+            attempts: Attempts::default(),
+        }
+    }
+
+    /// Return a node that runs this node again when it fails, until it fails `attempts` times,
+    /// waiting between attempts as specified by `backoff`.
+    /// If the waits are measured in time, a [`SystemClock`] is used unless changed through
+    /// [`RetryWithBackoff::with_clock`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// let mut runner = Runner::new(
+    ///     async_action(|tries: &mut u32| {
+    ///         *tries += 1;
+    ///         Status::Failure
+    ///     })
+    ///     .retry_with_backoff(3, Backoff::Linear(Wait::Ticks(1))),
+    /// );
+    ///
+    /// let mut tries = 0;
+    /// let mut ticks = 1;
+    ///
+    /// while runner.tick(&mut tries) == Status::Running {
+    ///     ticks += 1;
+    /// }
+    ///
+    /// assert_eq!(tries, 3);
+    /// assert_eq!(ticks, 6); // 3 attempts, waiting 1 and then 2 ticks in between
+    /// ```
+    #[inline]
+    fn retry_with_backoff(self, attempts: u32, backoff: Backoff) -> RetryWithBackoff<Self> {
+        RetryWithBackoff {
+            bhv: self,
+            max: attempts,
+            backoff,
+            clock: SystemClock::new(),
+            attempts: Attempts::default(),
+            waiting: None,
+        }
+    }
+
+    /// Return a node that runs this node, failing and halting it if it runs for longer than `limit`.
+    /// The time is measured by a [`SystemClock`] unless changed through [`Timeout::with_clock`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use bhv::*;
+    ///
+    /// let clock = MockClock::new();
+    ///
+    /// let mut runner = Runner::new(
+    ///     async_action(|_| Status::Running) // never finishes on its own
+    ///         .timeout(Duration::from_secs(2))
+    ///         .with_clock(clock.clone()),
+    /// );
+    ///
+    /// assert_eq!(runner.tick(&mut ()), Status::Running);
+    /// clock.advance(Duration::from_secs(1));
+    /// assert_eq!(runner.tick(&mut ()), Status::Running);
+    /// clock.advance(Duration::from_secs(1));
+    /// assert_eq!(runner.tick(&mut ()), Status::Failure);
+    /// ```
+    #[inline]
+    fn timeout(self, limit: Duration) -> Timeout<Self> {
+        Timeout {
+            bhv: self,
+            limit,
+            clock: SystemClock::new(),
+            deadline: None,
+        }
+    }
+
+    /// Return a node that waits for `delay` before running this node.
+    /// The time is measured by a [`SystemClock`] unless changed through [`Delay::with_clock`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use bhv::*;
+    ///
+    /// let clock = MockClock::new();
+    ///
+    /// let mut runner = Runner::new(
+    ///     action(|v| *v += 1)
+    ///         .delay(Duration::from_millis(500))
+    ///         .with_clock(clock.clone()),
+    /// );
+    ///
+    /// let mut ctx = 0;
+    ///
+    /// assert_eq!(runner.tick(&mut ctx), Status::Running);
+    /// clock.advance(Duration::from_millis(500));
+    /// assert_eq!(runner.tick(&mut ctx), Status::Success);
+    /// assert_eq!(ctx, 1);
+    /// ```
+    #[inline]
+    fn delay(self, delay: Duration) -> Delay<Self> {
+        Delay {
+            bhv: self,
+            delay,
+            clock: SystemClock::new(),
+            ready_at: None,
+        }
+    }
+
+    /// Return a node that runs this node, but fails without running it for `cooldown` after
+    /// this node completes.
+    /// The time is measured by a [`SystemClock`] unless changed through [`Cooldown::with_clock`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use bhv::*;
+    ///
+    /// let clock = MockClock::new();
+    ///
+    /// let mut runner = Runner::new(
+    ///     action(|shots| *shots += 1)
+    ///         .cooldown(Duration::from_secs(1))
+    ///         .with_clock(clock.clone()),
+    /// );
+    ///
+    /// let mut shots = 0;
+    ///
+    /// assert_eq!(runner.tick(&mut shots), Status::Success);
+    /// assert_eq!(runner.tick(&mut shots), Status::Failure); // still reloading
+    /// clock.advance(Duration::from_secs(1));
+    /// assert_eq!(runner.tick(&mut shots), Status::Success);
+    /// assert_eq!(shots, 2);
+    /// ```
+    ///
+    /// A cooldown too long to be measured lasts until the end of time.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use bhv::*;
+    ///
+    /// let clock = MockClock::new();
+    /// clock.set(Duration::from_secs(60));
+    ///
+    /// let mut runner = Runner::new(
+    ///     action(|shots| *shots += 1)
+    ///         .cooldown(Duration::MAX)
+    ///         .with_clock(clock.clone()),
+    /// );
+    ///
+    /// let mut shots = 0;
+    ///
+    /// assert_eq!(runner.tick(&mut shots), Status::Success);
+    /// clock.advance(Duration::MAX);
+    /// assert_eq!(runner.tick(&mut shots), Status::Failure);
+    /// assert_eq!(shots, 1);
+    /// ```
+    #[inline]
+    fn cooldown(self, cooldown: Duration) -> Cooldown<Self> {
+        Cooldown {
+            bhv: self,
+            cooldown,
+            clock: SystemClock::new(),
+            ready_at: None,
+            running: false,
+        }
+    }
+
+    /// Return a node that runs this node at most `per_second` times per second, returning
+    /// [`Status::Running`] when the node is skipped. The runs are spread evenly, so the node runs at
+    /// most once every `1 / per_second` seconds.
+    /// The time is measured by a [`SystemClock`] unless changed through [`Throttle::with_clock`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `per_second` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use bhv::*;
+    ///
+    /// let clock = MockClock::new();
+    ///
+    /// let mut runner = Runner::new(
+    ///     action(|scans| *scans += 1)
+    ///         .throttle(10) // a scan every 100ms at most
+    ///         .with_clock(clock.clone()),
+    /// );
+    ///
+    /// let mut scans = 0;
+    ///
+    /// for _ in 0..10 {
+    ///     runner.tick(&mut scans);
+    ///     clock.advance(Duration::from_millis(50));
+    /// }
+    ///
+    /// assert_eq!(scans, 5);
+    /// ```
+    #[inline]
+    fn throttle(self, per_second: u32) -> Throttle<Self> {
+        assert!(per_second > 0, "a throttled node should be able to run at least once per second");
+
+        Throttle {
+            bhv: self,
+            interval: Duration::from_secs(1) / per_second,
+            clock: SystemClock::new(),
+            next: None,
+        }
+    }
+
+    /// Return a node that runs this node as is, but is named `label` when inspecting the tree,
+    /// such as when exporting it through [`crate::to_dot`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// let attack = action(|hp: &mut i32| *hp -= 10).label("attack");
+    ///
+    /// assert_eq!(attack.kind(), "Action");
+    /// assert_eq!(attack.name(), "attack");
+    /// ```
+    #[inline]
+    fn label(self, label: impl Into<Cow<'static, str>>) -> Label<Self> {
+        Label {
+            bhv: self,
+            label: label.into(),
+        }
+    }
+
+    /// Return a node that reports the lifecycle of this node and its descendants to `observer`.
+    /// The observer can be accessed through [`Observed::observer`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Log(Vec<String>);
+    ///
+    /// impl TreeObserver for Log {
+    ///     fn on_status(&mut self, node: &NodeInfo<'_>, status: Status) {
+    ///         self.0.push(format!("{:?} {} {:?}", node.path, node.name, status));
+    ///     }
+    /// }
+    ///
+    /// let mut tree = sel! {
+    ///     cond(|v| *v > 10).label("is big"),
+    ///     action(|v| *v += 1).label("grow"),
+    /// }
+    /// .observed(Log::default());
+    ///
+    /// tree.update(&mut 5);
+    ///
+    /// assert_eq!(
+    ///     tree.observer().0,
+    ///     ["[0] is big Failure", "[1] grow Success", "[] Sel Success"],
+    /// );
+    /// ```
+    #[inline]
+    fn observed<O: TreeObserver + 'static>(self, observer: O) -> Observed<Self, O> {
+        Observed::new(self, observer)
+    }
+
+    /// Return a node that reports the lifecycle of this node and its descendants as spans and
+    /// events of the [`tracing`] crate. See [`crate::TracingObserver`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    /// # use std::{fmt, sync::{Arc, Mutex}};
+    /// # use tracing::{field::{Field, Visit}, span, Event, Metadata, Subscriber};
+    /// #
+    /// # // a subscriber writing each span and event on its own line, indented by the spans it is in
+    /// # #[derive(Clone, Default)]
+    /// # struct Capture(Arc<Mutex<(Vec<String>, usize)>>);
+    /// #
+    /// # struct Fields(String);
+    /// #
+    /// # impl Visit for Fields {
+    /// #     fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+    /// #         self.0 += &format!(" {}={:?}", field, value);
+    /// #     }
+    /// # }
+    /// #
+    /// # impl Subscriber for Capture {
+    /// #     fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+    /// #     fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+    /// #         let mut fields = Fields(span.metadata().name().to_string());
+    /// #         span.record(&mut fields);
+    /// #         let mut c = self.0.lock().unwrap();
+    /// #         let line = format!("{}{}", "  ".repeat(c.1), fields.0);
+    /// #         c.0.push(line);
+    /// #         span::Id::from_u64(c.0.len() as u64)
+    /// #     }
+    /// #     fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+    /// #     fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+    /// #     fn event(&self, event: &Event<'_>) {
+    /// #         let mut fields = Fields(String::new());
+    /// #         event.record(&mut fields);
+    /// #         let mut c = self.0.lock().unwrap();
+    /// #         let line = format!("{}{}", "  ".repeat(c.1), fields.0.trim_start());
+    /// #         c.0.push(line);
+    /// #     }
+    /// #     fn enter(&self, _: &span::Id) { self.0.lock().unwrap().1 += 1; }
+    /// #     fn exit(&self, _: &span::Id) { self.0.lock().unwrap().1 -= 1; }
+    /// # }
+    ///
+    /// let tree = seq! {
+    ///     cond(|v| *v > 0).label("is positive"),
+    ///     action(|v| *v -= 1).label("decrement"),
+    /// }
+    /// .traced();
+    ///
+    /// let capture = Capture::default();
+    /// let mut v = 5;
+    ///
+    /// tracing::subscriber::with_default(capture.clone(), || tree.execute(&mut v));
+    ///
+    /// assert_eq!(v, 4);
+    /// assert_eq!(capture.0.lock().unwrap().0[..6], [
+    ///     r#"node id=0 kind="Seq" name="Seq" path=[]"#,
+    ///     r#"  node id=1 kind="Cond" name="is positive" path=[0]"#,
+    ///     r#"    message=status status=Success"#,
+    ///     r#"  node id=2 kind="Action" name="decrement" path=[1]"#,
+    ///     r#"    message=status status=Success"#,
+    ///     r#"  message=reset id=1 name="is positive" status=Success"#,
+    /// ]);
+    /// ```
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn traced(self) -> Observed<Self, crate::TracingObserver> {
+        Observed::new(self, crate::TracingObserver::new())
+    }
+
+    /// Return a node that runs this node on a part of a larger context, selected by `lens`.
+    /// This way, nodes that work on some context type can be used in a tree of another context type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Navigation {
+    ///     steps: u32,
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Agent {
+    ///     nav: Navigation,
+    ///     arrived: bool,
+    /// }
+    ///
+    /// let walk = action(|n: &mut Navigation| n.steps += 1).repeat(3);
+    ///
+    /// let tree = seq! {
+    ///     walk.with_context(|a: &mut Agent| &mut a.nav),
+    ///     action(|a: &mut Agent| a.arrived = true),
+    /// };
+    ///
+    /// let mut agent = Agent::default();
+    /// tree.execute(&mut agent);
+    ///
+    /// assert_eq!(agent.nav.steps, 3);
+    /// assert!(agent.arrived);
+    /// ```
+    #[inline]
+    fn with_context<Outer, F>(self, lens: F) -> WithContext<Self, Outer, F>
+        where
+            F: Fn(&mut Outer) -> &mut Self::Context,
+    {
+        WithContext {
+            bhv: self,
+            lens,
+            _tag: PhantomData,
+        }
+    }
+
+    /// Return a node that runs this node inside of its own [`Blackboard`] scope.
+    /// Values stored by this node are not visible outside of it and are discarded when it is done.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// const TARGET: Key<&str> = Key::new("target");
+    ///
+    /// let tree = seq! {
+    ///     bb_set(TARGET, "enemy"),
+    ///     seq! {
+    ///         bb_set(TARGET, "ally"),
+    ///         bb_cond(TARGET, |t| *t == "ally"),
+    ///     }.scoped(),
+    ///     bb_cond(TARGET, |t| *t == "enemy"),
+    /// };
+    ///
+    /// assert_eq!(format!("{:?}", tree), "Seq[Action, Scoped[Seq[Action, Cond]], Cond]");
+    /// assert_eq!(tree.execute(&mut Blackboard::new()), true);
+    /// ```
+    #[inline]
+    fn scoped(self) -> Scoped<Self>
+        where
+            Self::Context: AsMut<Blackboard>,
+    {
+        Scoped {
+            bhv: self,
+            scope: Default::default(),
+        }
+    }
 }
 
 impl<B> BhvExt for B where B: Bhv + Sized {}
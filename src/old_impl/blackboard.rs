@@ -0,0 +1,340 @@
+use std::{any::Any, collections::HashMap, fmt, marker::PhantomData};
+
+use crate::{action, cond, old_impl::observe, Action, Bhv, Cond, NodeMut, NodeRef, Status};
+
+type Scope = HashMap<&'static str, Box<dyn Any>>;
+
+/// A typed key used to access values of type `T` inside of a [`Blackboard`].
+///
+/// Keys are usually declared as constants and shared between the nodes that need them.
+pub struct Key<T> {
+    name: &'static str,
+    _tag: PhantomData<fn() -> T>,
+}
+
+/// A storage of values of arbitrary types, shared between nodes of a tree through typed [`Key`]s.
+///
+/// The values are stored in nested scopes. Lookups start from the innermost scope and fall
+/// through to the outer ones, while writes always go to the innermost scope. Subtrees that need
+/// private values can be run in their own scope using [`crate::BhvExt::scoped`].
+///
+/// Nodes that work with a blackboard require their context to implement `AsRef<Blackboard>`
+/// and/or `AsMut<Blackboard>`, so a blackboard can be a member of a larger context type.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// const HEALTH: Key<u32> = Key::new("health");
+/// const FLEEING: Key<bool> = Key::new("fleeing");
+///
+/// let tree = sel! {
+///     seq! {
+///         bb_cond(HEALTH, |h| *h < 20),
+///         bb_set(FLEEING, true),
+///     },
+///     bb_set(FLEEING, false),
+/// };
+///
+/// let mut bb = Blackboard::new();
+/// bb.set(HEALTH, 10);
+///
+/// tree.execute(&mut bb);
+///
+/// assert_eq!(bb.get(FLEEING), Some(&true));
+/// ```
+pub struct Blackboard {
+    scopes: Vec<Scope>,
+}
+
+/// A decorator that runs the given node inside of its own [`Blackboard`] scope.
+///
+/// Values set by the node are visible only to the node and are discarded once it is done.
+pub struct Scoped<B: Bhv> {
+    pub(crate) bhv: B,
+    pub(crate) scope: Scope,
+}
+
+impl<T> Key<T> {
+    /// Create a key with the given name.
+    #[inline]
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _tag: PhantomData,
+        }
+    }
+
+    /// The name of the key.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T> Clone for Key<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Key<T> {}
+
+impl<T> fmt::Debug for Key<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Key").field(&self.name).finish()
+    }
+}
+
+impl Blackboard {
+    /// Create an empty blackboard with a single scope.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![Scope::new()],
+        }
+    }
+
+    /// Get the value stored under `key`, looking from the innermost scope outwards.
+    ///
+    /// Returns `None` if there is no value, or if the value found is not of type `T`.
+    pub fn get<T: 'static>(&self, key: Key<T>) -> Option<&T> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.get(key.name))
+            .and_then(|v| v.downcast_ref())
+    }
+
+    /// Get a mutable reference to the value stored under `key`, looking from the innermost scope
+    /// outwards.
+    ///
+    /// Returns `None` if there is no value, or if the value found is not of type `T`.
+    pub fn get_mut<T: 'static>(&mut self, key: Key<T>) -> Option<&mut T> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|s| s.get_mut(key.name))
+            .and_then(|v| v.downcast_mut())
+    }
+
+    /// Check whether there is a value of type `T` stored under `key`.
+    #[inline]
+    pub fn contains<T: 'static>(&self, key: Key<T>) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Store `value` under `key` in the innermost scope, replacing any previous value.
+    pub fn set<T: 'static>(&mut self, key: Key<T>, value: T) {
+        self.innermost().insert(key.name, Box::new(value));
+    }
+
+    /// Remove the value stored under `key` from the innermost scope and return it.
+    ///
+    /// Values of the outer scopes are never removed.
+    pub fn remove<T: 'static>(&mut self, key: Key<T>) -> Option<T> {
+        let scope = self.innermost();
+
+        if scope.get(key.name)?.is::<T>() {
+            scope.remove(key.name)?.downcast().ok().map(|v| *v)
+        } else {
+            None
+        }
+    }
+
+    /// Open a new innermost scope.
+    #[inline]
+    pub fn push_scope(&mut self) {
+        self.scopes.push(Scope::new());
+    }
+
+    /// Close the innermost scope, discarding its values. The outermost scope is never closed.
+    #[inline]
+    pub fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    #[inline]
+    fn innermost(&mut self) -> &mut Scope {
+        // there is always at least one scope
+        self.scopes.last_mut().unwrap()
+    }
+}
+
+impl Default for Blackboard {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsRef<Blackboard> for Blackboard {
+    #[inline]
+    fn as_ref(&self) -> &Blackboard {
+        self
+    }
+}
+
+impl AsMut<Blackboard> for Blackboard {
+    #[inline]
+    fn as_mut(&mut self) -> &mut Blackboard {
+        self
+    }
+}
+
+impl<B> Scoped<B>
+    where
+        B: Bhv,
+        B::Context: AsMut<Blackboard>,
+{
+    /// Run `f` with the scope of this node pushed on the blackboard.
+    fn in_scope<R>(&mut self, ctx: &mut B::Context, f: impl FnOnce(&mut B, &mut B::Context) -> R) -> R {
+        let bb = ctx.as_mut();
+        bb.scopes.push(std::mem::take(&mut self.scope));
+
+        let r = f(&mut self.bhv, ctx);
+
+        let bb = ctx.as_mut();
+        // `pop_scope` is not used, as it never pops the outermost scope
+        if bb.scopes.len() > 1 {
+            self.scope = bb.scopes.pop().unwrap_or_default();
+        }
+
+        r
+    }
+}
+
+impl<B> Bhv for Scoped<B>
+    where
+        B: Bhv,
+        B::Context: AsMut<Blackboard>,
+{
+    type Context = B::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Scoped"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        let s = self.in_scope(ctx, |bhv, ctx| observe::update(0, bhv, ctx));
+
+        if s != Status::Running {
+            self.scope.clear();
+        }
+
+        s
+    }
+
+    fn reset(&mut self, _status: Status) {
+        observe::reset(0, &mut self.bhv, _status);
+        self.scope.clear();
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.in_scope(ctx, |bhv, ctx| observe::halt(0, bhv, ctx));
+        self.scope.clear();
+    }
+}
+
+impl<B> fmt::Debug for Scoped<B>
+    where
+        B: Bhv,
+        B::Context: AsMut<Blackboard>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+/// Adapt a predicate on the value stored under `key` into a behavior, returning [`Status::Success`]
+/// if the value exists and the predicate returns `true`, and [`Status::Failure`] otherwise.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// const AMMO: Key<u32> = Key::new("ammo");
+///
+/// let mut bb = Blackboard::new();
+/// assert_eq!(bb_cond(AMMO, |a| *a > 0).execute(&mut bb), false);
+///
+/// bb.set(AMMO, 3);
+/// assert_eq!(bb_cond(AMMO, |a| *a > 0).execute(&mut bb), true);
+/// ```
+#[inline]
+pub fn bb_cond<Ctx, T, P>(key: Key<T>, pred: P) -> Cond<Ctx, impl Fn(&Ctx) -> bool>
+    where
+        Ctx: AsRef<Blackboard>,
+        T: 'static,
+        P: Fn(&T) -> bool,
+{
+    cond(move |ctx: &Ctx| ctx.as_ref().get(key).is_some_and(&pred))
+}
+
+/// Adapt storing a copy of `value` under `key` into a behavior, returning [`Status::Success`]
+/// on every call to [`Bhv::update`].
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// const TARGET: Key<(i32, i32)> = Key::new("target");
+///
+/// let mut bb = Blackboard::new();
+/// bb_set(TARGET, (4, 2)).execute(&mut bb);
+///
+/// assert_eq!(bb.get(TARGET), Some(&(4, 2)));
+/// ```
+#[inline]
+pub fn bb_set<Ctx, T>(key: Key<T>, value: T) -> Action<Ctx, impl FnMut(&mut Ctx)>
+    where
+        Ctx: AsMut<Blackboard>,
+        T: Clone + 'static,
+{
+    action(move |ctx: &mut Ctx| ctx.as_mut().set(key, value.clone()))
+}
+
+/// Adapt removing the value stored under `key` into a behavior, returning [`Status::Success`]
+/// on every call to [`Bhv::update`].
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// const TARGET: Key<(i32, i32)> = Key::new("target");
+///
+/// let mut bb = Blackboard::new();
+/// bb.set(TARGET, (4, 2));
+///
+/// bb_remove(TARGET).execute(&mut bb);
+///
+/// assert!(!bb.contains(TARGET));
+/// ```
+#[inline]
+pub fn bb_remove<Ctx, T>(key: Key<T>) -> Action<Ctx, impl FnMut(&mut Ctx)>
+    where
+        Ctx: AsMut<Blackboard>,
+        T: 'static,
+{
+    action(move |ctx: &mut Ctx| {
+        ctx.as_mut().remove(key);
+    })
+}
@@ -1,6 +1,6 @@
-use std::marker::PhantomData;
+use std::{fmt, marker::PhantomData};
 
-use crate::{Bhv, Status};
+use crate::{old_impl::observe, Bhv, NodeMut, NodeRef, Status};
 
 // TODO:
 // BhvExt::then specialization for Seq
@@ -23,6 +23,15 @@ pub(crate) struct List<Ctx, Policy>
     _tag: PhantomData<Policy>,
 }
 
+pub(crate) struct ReactiveList<Ctx, Policy>
+    where
+        Policy: StatusPolicy,
+{
+    nodes: Vec<Box<dyn Bhv<Context=Ctx>>>,
+    running: Option<usize>,
+    _tag: PhantomData<Policy>,
+}
+
 /// A selector is a behavior node composed of a list of nodes that are run until one of them succeeds,
 /// in which case the node also succeeds. If none of the nodes succeeds, this node fails.
 pub struct Sel<Ctx>(pub(crate) List<Ctx, SelPolicy>);
@@ -31,6 +40,16 @@ pub struct Sel<Ctx>(pub(crate) List<Ctx, SelPolicy>);
 /// in which case the node also fails. If none of the nodes fails, this node succeeds.
 pub struct Seq<Ctx>(pub(crate) List<Ctx, SeqPolicy>);
 
+/// A reactive selector is a selector that runs its nodes from the start on every update, instead of
+/// resuming from the node that is running. If a node before the running one succeeds, the running
+/// node is halted.
+pub struct ReactiveSel<Ctx>(pub(crate) ReactiveList<Ctx, SelPolicy>);
+
+/// A reactive sequence is a sequence that runs its nodes from the start on every update, instead of
+/// resuming from the node that is running. If a node before the running one fails, the running
+/// node is halted.
+pub struct ReactiveSeq<Ctx>(pub(crate) ReactiveList<Ctx, SeqPolicy>);
+
 impl<Ctx> Sel<Ctx> {
     #[inline]
     pub fn with_nodes(nodes: Vec<Box<dyn Bhv<Context=Ctx>>>) -> Self {
@@ -53,6 +72,28 @@ impl<Ctx> Seq<Ctx> {
     }
 }
 
+impl<Ctx> ReactiveSel<Ctx> {
+    #[inline]
+    pub fn with_nodes(nodes: Vec<Box<dyn Bhv<Context=Ctx>>>) -> Self {
+        Self(ReactiveList {
+            nodes,
+            running: None,
+            _tag: PhantomData,
+        })
+    }
+}
+
+impl<Ctx> ReactiveSeq<Ctx> {
+    #[inline]
+    pub fn with_nodes(nodes: Vec<Box<dyn Bhv<Context=Ctx>>>) -> Self {
+        Self(ReactiveList {
+            nodes,
+            running: None,
+            _tag: PhantomData,
+        })
+    }
+}
+
 impl<Ctx, Policy> Bhv for List<Ctx, Policy>
     where
         Policy: StatusPolicy,
@@ -66,7 +107,7 @@ impl<Ctx, Policy> Bhv for List<Ctx, Policy>
                 self.reset(Policy::STATUS);
                 return Policy::STATUS;
             } else {
-                let s = self.nodes[self.current].update(ctx);
+                let s = observe::update(self.current, &mut self.nodes[self.current], ctx);
 
                 if s == Policy::STATUS {
                     self.current += 1;
@@ -86,15 +127,120 @@ impl<Ctx, Policy> Bhv for List<Ctx, Policy>
 
         self.nodes[..count]
             .iter_mut()
-            .for_each(|n| n.reset(Policy::STATUS));
+            .enumerate()
+            .for_each(|(i, n)| observe::reset(i, n, Policy::STATUS));
 
         self.current = 0;
     }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        if let Some(n) = self.nodes.get_mut(self.current) {
+            observe::halt(self.current, n, ctx);
+        }
+
+        self.reset(Policy::STATUS);
+    }
+
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.current as u64]
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match *state {
+            [current] if (current as usize) < self.nodes.len() => {
+                self.current = current as usize;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<Ctx, Policy> ReactiveList<Ctx, Policy>
+    where
+        Policy: StatusPolicy,
+{
+    /// Halt the running node, unless it is the one at `index`.
+    fn halt_running_except(&mut self, index: usize, ctx: &mut Ctx) {
+        if let Some(i) = self.running.take() {
+            if i != index {
+                observe::halt(i, &mut self.nodes[i], ctx);
+            }
+        }
+    }
+}
+
+impl<Ctx, Policy> Bhv for ReactiveList<Ctx, Policy>
+    where
+        Policy: StatusPolicy,
+{
+    type Context = Ctx;
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        for i in 0..self.nodes.len() {
+            let s = observe::update(i, &mut self.nodes[i], ctx);
+
+            if s == Policy::STATUS {
+                // earlier nodes are checked again on the next update
+                observe::reset(i, &mut self.nodes[i], s);
+                if self.running == Some(i) {
+                    self.running = None;
+                }
+            } else {
+                self.halt_running_except(i, ctx);
+
+                if s == Status::Running {
+                    self.running = Some(i);
+                } else {
+                    observe::reset(i, &mut self.nodes[i], s);
+                }
+
+                return s;
+            }
+        }
+
+        Policy::STATUS
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        if let Some(i) = self.running.take() {
+            observe::halt(i, &mut self.nodes[i], ctx);
+        }
+    }
+
+    fn save_state(&self) -> Vec<u64> {
+        self.running.iter().map(|&i| i as u64).collect()
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match *state {
+            [] => self.running = None,
+            [i] if (i as usize) < self.nodes.len() => self.running = Some(i as usize),
+            _ => return false,
+        }
+
+        true
+    }
 }
 
 impl<Ctx> Bhv for Sel<Ctx> {
     type Context = Ctx;
 
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Sel"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.0.nodes.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.0.nodes.iter_mut().for_each(|n| f(NodeMut::new(n)))
+    }
+
     #[inline]
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
         self.0.update(ctx)
@@ -104,11 +250,41 @@ impl<Ctx> Bhv for Sel<Ctx> {
     fn reset(&mut self, _status: Status) {
         self.0.reset(_status)
     }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        self.0.save_state()
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        self.0.load_state(state)
+    }
 }
 
 impl<Ctx> Bhv for Seq<Ctx> {
     type Context = Ctx;
 
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Seq"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.0.nodes.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.0.nodes.iter_mut().for_each(|n| f(NodeMut::new(n)))
+    }
+
     #[inline]
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
         self.0.update(ctx)
@@ -118,6 +294,123 @@ impl<Ctx> Bhv for Seq<Ctx> {
     fn reset(&mut self, _status: Status) {
         self.0.reset(_status)
     }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        self.0.save_state()
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        self.0.load_state(state)
+    }
+}
+
+impl<Ctx> Bhv for ReactiveSel<Ctx> {
+    type Context = Ctx;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "ReactiveSel"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.0.nodes.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.0.nodes.iter_mut().for_each(|n| f(NodeMut::new(n)))
+    }
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        self.0.update(ctx)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        self.0.save_state()
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        self.0.load_state(state)
+    }
+}
+
+impl<Ctx> Bhv for ReactiveSeq<Ctx> {
+    type Context = Ctx;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "ReactiveSeq"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.0.nodes.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.0.nodes.iter_mut().for_each(|n| f(NodeMut::new(n)))
+    }
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        self.0.update(ctx)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        self.0.save_state()
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        self.0.load_state(state)
+    }
+}
+
+impl<Ctx> fmt::Debug for Sel<Ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<Ctx> fmt::Debug for Seq<Ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<Ctx> fmt::Debug for ReactiveSel<Ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<Ctx> fmt::Debug for ReactiveSeq<Ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
 }
 
 impl StatusPolicy for SelPolicy {
@@ -186,4 +479,86 @@ macro_rules! seq {
             vec![$(Box::new($x)),+],
         )
     };
-}
\ No newline at end of file
+}
+
+/// A macro used to create a reactive selector from a list of behaviors.
+/// Reactive selectors run every behavior until one of them succeeds, starting over from the first
+/// behavior on every update.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// struct Guard {
+///     alarm: bool,
+///     patrolled: u32,
+/// }
+///
+/// let tree = reactive_sel! {
+///     cond(|g: &Guard| g.alarm), // checked again while patrolling
+///     async_action(|g: &mut Guard| {
+///         g.patrolled += 1;
+///         if g.patrolled == 3 {
+///             g.alarm = true;
+///         }
+///         Status::Running
+///     }),
+/// };
+///
+/// let mut guard = Guard { alarm: false, patrolled: 0 };
+/// assert!(tree.execute(&mut guard) == true);
+/// assert_eq!(guard.patrolled, 3);
+/// ```
+#[macro_export]
+macro_rules! reactive_sel {
+    () => {
+        compile_error!("`reactive_sel` should have at least one argument!")
+    };
+    ($($x:expr),+$(,)?) => {
+        $crate::ReactiveSel::with_nodes(
+            vec![$(Box::new($x)),+],
+        )
+    };
+}
+
+/// A macro used to create a reactive sequence from a list of behaviors.
+/// Reactive sequences run every behavior until one of them fails, starting over from the first
+/// behavior on every update.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// struct Hunter {
+///     target_visible: bool,
+///     distance: u32,
+/// }
+///
+/// let tree = reactive_seq! {
+///     cond(|h: &Hunter| h.target_visible), // stop chasing if the target disappears
+///     async_action(|h: &mut Hunter| {
+///         h.distance -= 1;
+///         if h.distance == 5 {
+///             h.target_visible = false;
+///         }
+///         Status::Running
+///     }),
+/// };
+///
+/// let mut hunter = Hunter { target_visible: true, distance: 10 };
+/// assert!(tree.execute(&mut hunter) == false);
+/// assert_eq!(hunter.distance, 5);
+/// ```
+#[macro_export]
+macro_rules! reactive_seq {
+    () => {
+        compile_error!("`reactive_seq` should have at least one argument!")
+    };
+    ($($x:expr),+$(,)?) => {
+        $crate::ReactiveSeq::with_nodes(
+            vec![$(Box::new($x)),+],
+        )
+    };
+}
@@ -1,5 +1,10 @@
+use std::borrow::Cow;
+
+use crate::{NodeMut, NodeRef};
+
 /// An enum type representing the outcome of calling [`Bhv::update`].
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Status {
     /// The behavior is still running.
     Running,
@@ -22,6 +27,96 @@ pub trait Bhv {
     /// Defaults to nothing.
     fn reset(&mut self, _status: Status) {}
 
+    /// Interrupt the node while it is running, such as when a higher-priority branch of the tree
+    /// takes over, and bring it back to its initial state.
+    /// Composite and decorator nodes forward this to their running children.
+    /// Defaults to nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Robot {
+    ///     moving: bool,
+    ///     target_seen: bool,
+    /// }
+    ///
+    /// // A long-running action that needs to stop the motors when interrupted.
+    /// struct MoveForward;
+    ///
+    /// impl Bhv for MoveForward {
+    ///     type Context = Robot;
+    ///
+    ///     fn update(&mut self, ctx: &mut Self::Context) -> Status {
+    ///         ctx.moving = true;
+    ///         Status::Running
+    ///     }
+    ///
+    ///     fn halt(&mut self, ctx: &mut Self::Context) {
+    ///         ctx.moving = false;
+    ///     }
+    /// }
+    ///
+    /// let tree = when_any! {
+    ///     MoveForward,
+    ///     cond(|r: &Robot| r.target_seen),
+    ///     action(|r: &mut Robot| r.target_seen = true), // spotted on the first update
+    /// };
+    ///
+    /// let mut robot = Robot::default();
+    /// tree.execute(&mut robot);
+    ///
+    /// assert!(!robot.moving);
+    /// ```
+    fn halt(&mut self, _ctx: &mut Self::Context) {}
+
+    /// A short name of the type of this node, such as `"Seq"` or `"Repeat"`.
+    /// Defaults to `"Leaf"`.
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Leaf"
+    }
+
+    /// A human-readable description of this node, such as `"Repeat(3)"` or a label given through
+    /// [`crate::BhvExt::label`].
+    /// Defaults to [`Bhv::kind`].
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.kind())
+    }
+
+    /// Call `f` on each child of this node, in order. Nodes that have children should override
+    /// this, so that the structure of the tree can be inspected through [`NodeRef`].
+    /// Defaults to nothing.
+    #[inline]
+    fn visit_children<'a>(&'a self, _f: &mut dyn FnMut(NodeRef<'a>)) {}
+
+    /// Call `f` on each child of this node, in the same order as [`Bhv::visit_children`]. Nodes
+    /// that have children should override this, so that their state can be restored through
+    /// [`crate::restore`].
+    /// Defaults to nothing.
+    #[inline]
+    fn visit_children_mut(&mut self, _f: &mut dyn FnMut(NodeMut<'_>)) {}
+
+    /// The runtime state of this node, not including its children, such as the index of the
+    /// running child or the number of repetitions so far. Nodes that keep state between updates
+    /// should override this, so that it can be saved through [`crate::snapshot`].
+    /// Defaults to no state.
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        Vec::new()
+    }
+
+    /// Restore the runtime state of this node from the result of [`Bhv::save_state`].
+    /// Returns `false` if the state is not valid for this node.
+    /// Defaults to accepting only an empty state.
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        state.is_empty()
+    }
+
     /// Update the node until it returns a value different from [`Status::Running`].
     ///
     /// Useful for running a whole tree once built.
@@ -40,3 +135,52 @@ pub trait Bhv {
         }
     }
 }
+
+impl<B: Bhv + ?Sized> Bhv for Box<B> {
+    type Context = B::Context;
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        (**self).update(ctx)
+    }
+
+    #[inline]
+    fn reset(&mut self, status: Status) {
+        (**self).reset(status)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        (**self).halt(ctx)
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        (**self).kind()
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        (**self).name()
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        (**self).visit_children(f)
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        (**self).visit_children_mut(f)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        (**self).save_state()
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        (**self).load_state(state)
+    }
+}
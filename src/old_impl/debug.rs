@@ -0,0 +1,181 @@
+use crate::{NodeId, NodeInfo, Status, TreeObserver};
+
+/// A condition under which a [`Debugger`] pauses the tree.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Breakpoint {
+    /// Pause before the node at the given path is updated.
+    Enter(Vec<usize>),
+    /// Pause after the node at the given path returns the given status.
+    Status(Vec<usize>, Status),
+}
+
+/// What a [`Debugger`] does after the host hands control back to the tree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resume {
+    /// Run until the next breakpoint.
+    Continue,
+    /// Pause again when the next node is entered or returns.
+    Step,
+}
+
+/// A node on the active path of a tree, as seen by a [`Debugger`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// The identifier of the node.
+    pub id: NodeId,
+    /// The kind of the node. See [`crate::Bhv::kind`].
+    pub kind: &'static str,
+    /// The name of the node. See [`crate::Bhv::name`].
+    pub name: String,
+}
+
+/// The state of a tree paused by a [`Debugger`], passed to the host.
+#[derive(Clone, Copy, Debug)]
+pub struct Paused<'a> {
+    /// The node where the tree is paused.
+    pub node: NodeInfo<'a>,
+    /// The status returned by the node, or `None` if the node is about to be updated.
+    pub status: Option<Status>,
+    /// The nodes being updated, from the root down to the paused node.
+    pub active: &'a [Frame],
+}
+
+/// A [`TreeObserver`] that pauses a tree on breakpoints or one node at a time, handing control to
+/// the host through a callback.
+///
+/// While paused, the tree waits for the callback to return, so the callback can inspect the
+/// [`Paused`] state, wait for a command from a REPL or a channel, and decide how to [`Resume`].
+///
+/// # Example
+///
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// use bhv::*;
+///
+/// let tree = seq! {
+///     action(|v: &mut i32| *v += 1).label("inc"),
+///     cond(|v: &i32| *v > 1).label("check"),
+/// };
+///
+/// let log = Rc::new(RefCell::new(Vec::new()));
+/// let host = log.clone();
+///
+/// let mut debugger = Debugger::new(move |p: &Paused<'_>| {
+///     let names: Vec<_> = p.active.iter().map(|f| f.name.clone()).collect();
+///     host.borrow_mut().push((names.join("/"), p.status));
+///
+///     // step through the nodes after the first stop
+///     Resume::Step
+/// });
+///
+/// debugger.break_on(Breakpoint::Status(vec![1], Status::Failure));
+///
+/// let mut runner = Runner::new(tree.observed(debugger));
+/// runner.tick(&mut 0);
+///
+/// assert_eq!(
+///     *log.borrow(),
+///     [
+///         ("Seq/check".to_string(), Some(Status::Failure)),
+///         ("Seq".to_string(), Some(Status::Failure)),
+///     ],
+/// );
+/// ```
+pub struct Debugger<F> {
+    breakpoints: Vec<Breakpoint>,
+    stepping: bool,
+    active: Vec<Frame>,
+    on_pause: F,
+}
+
+impl<F> Debugger<F>
+    where
+        F: FnMut(&Paused<'_>) -> Resume,
+{
+    /// Create a debugger with no breakpoints, which calls `on_pause` whenever the tree is paused.
+    #[inline]
+    pub fn new(on_pause: F) -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            stepping: false,
+            active: Vec::new(),
+            on_pause,
+        }
+    }
+
+    /// Add a breakpoint.
+    #[inline]
+    pub fn break_on(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    /// Remove every breakpoint.
+    #[inline]
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// The breakpoints of the debugger.
+    #[inline]
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Pause when the next node is entered or returns, regardless of breakpoints.
+    #[inline]
+    pub fn step(&mut self) {
+        self.stepping = true;
+    }
+
+    /// The nodes being updated, from the root down. Empty when the tree is not running.
+    #[inline]
+    pub fn active_path(&self) -> &[Frame] {
+        &self.active
+    }
+
+    fn pause(&mut self, node: &NodeInfo<'_>, status: Option<Status>) {
+        let paused = Paused {
+            node: *node,
+            status,
+            active: &self.active,
+        };
+
+        self.stepping = (self.on_pause)(&paused) == Resume::Step;
+    }
+}
+
+impl<F> TreeObserver for Debugger<F>
+    where
+        F: FnMut(&Paused<'_>) -> Resume,
+{
+    fn on_enter(&mut self, node: &NodeInfo<'_>) {
+        self.active.push(Frame {
+            id: node.id,
+            kind: node.kind,
+            name: node.name.to_string(),
+        });
+
+        let hit = self.breakpoints.iter().any(|b| match b {
+            Breakpoint::Enter(path) => path == node.path,
+            _ => false,
+        });
+
+        if self.stepping || hit {
+            self.pause(node, None);
+        }
+    }
+
+    fn on_status(&mut self, node: &NodeInfo<'_>, status: Status) {
+        let hit = self.breakpoints.iter().any(|b| match b {
+            Breakpoint::Status(path, s) => path == node.path && *s == status,
+            _ => false,
+        });
+
+        if self.stepping || hit {
+            self.pause(node, Some(status));
+        }
+
+        self.active.pop();
+    }
+}
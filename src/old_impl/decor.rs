@@ -1,4 +1,6 @@
-use crate::{Bhv, Status};
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+use crate::{old_impl::observe, Bhv, NodeMut, NodeRef, Status};
 
 /// A decorator that runs the given node until it's done and inverts
 /// the result.
@@ -17,7 +19,7 @@ pub struct Pass<B: Bhv>(pub(crate) B);
 pub struct Fail<B: Bhv>(pub(crate) B);
 
 /// A decorator that runs the given node if a given condition is true and returns the node's status.
-/// If the condition is not true, this returns [`Status::Failure`].
+/// If the condition is not true, this returns [`Status::Failure`], halting the node if it was running.
 #[derive(Clone)]
 pub struct RunIf<B, C>
     where
@@ -26,6 +28,7 @@ pub struct RunIf<B, C>
 {
     pub(crate) bhv: B,
     pub(crate) cond: C,
+    pub(crate) running: bool,
 }
 
 /// A decorator that runs the given node a certain number of times and returns its status.
@@ -61,11 +64,46 @@ pub struct RepeatUntilPass<B: Bhv>(pub(crate) B);
 #[derive(Clone)]
 pub struct RepeatUntilFail<B: Bhv>(pub(crate) B);
 
+/// A decorator that runs the given node on a part of a larger context, selected by a projection
+/// function. This allows nodes written for some context type to be used in trees of other context types.
+pub struct WithContext<B, Outer, F>
+    where
+        B: Bhv,
+        F: Fn(&mut Outer) -> &mut B::Context,
+{
+    pub(crate) bhv: B,
+    pub(crate) lens: F,
+    pub(crate) _tag: PhantomData<Outer>,
+}
+
+/// A decorator that gives a name to the given node, used when inspecting the tree.
+/// The node is run as is.
+#[derive(Clone)]
+pub struct Label<B: Bhv> {
+    pub(crate) bhv: B,
+    pub(crate) label: Cow<'static, str>,
+}
+
 impl<B: Bhv> Bhv for Inv<B> {
     type Context = B::Context;
 
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Inv"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.0))
+    }
+
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
-        match self.0.update(ctx) {
+        match observe::update(0, &mut self.0, ctx) {
             Status::Running => Status::Running,
             Status::Failure => Status::Success,
             Status::Success => Status::Failure,
@@ -73,37 +111,79 @@ impl<B: Bhv> Bhv for Inv<B> {
     }
 
     fn reset(&mut self, _status: Status) {
-        self.0.reset(_status)
+        observe::reset(0, &mut self.0, _status)
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.0, ctx)
     }
 }
 
 impl<B: Bhv> Bhv for Pass<B> {
     type Context = B::Context;
 
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Pass"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.0))
+    }
+
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
-        match self.0.update(ctx) {
+        match observe::update(0, &mut self.0, ctx) {
             Status::Failure => Status::Success,
             s => s,
         }
     }
 
     fn reset(&mut self, _status: Status) {
-        self.0.reset(_status)
+        observe::reset(0, &mut self.0, _status)
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.0, ctx)
     }
 }
 
 impl<B: Bhv> Bhv for Fail<B> {
     type Context = B::Context;
 
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Fail"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.0))
+    }
+
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
-        match self.0.update(ctx) {
+        match observe::update(0, &mut self.0, ctx) {
             Status::Success => Status::Failure,
             s => s,
         }
     }
 
     fn reset(&mut self, _status: Status) {
-        self.0.reset(_status)
+        observe::reset(0, &mut self.0, _status)
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.0, ctx)
     }
 }
 
@@ -113,28 +193,87 @@ impl<B, C> Bhv for RunIf<B, C>
         C: Fn(&B::Context) -> bool,
 {
     type Context = B::Context;
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "RunIf"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
         if (self.cond)(ctx) {
-            self.bhv.update(ctx)
+            let s = observe::update(0, &mut self.bhv, ctx);
+            self.running = s == Status::Running;
+            s
         } else {
+            self.halt(ctx);
             Status::Failure
         }
     }
     #[inline]
-    fn reset(&mut self, _status: Status) { self.bhv.reset(_status) }
+    fn reset(&mut self, _status: Status) { observe::reset(0, &mut self.bhv, _status) }
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        if self.running {
+            observe::halt(0, &mut self.bhv, ctx);
+            self.running = false;
+        }
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.running as u64]
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match *state {
+            [running @ (0 | 1)] => {
+                self.running = running == 1;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl<B: Bhv> Bhv for Repeat<B> {
     type Context = B::Context;
 
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Repeat"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Repeat({})", self.count))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
         if self.current >= self.count {
-            self.bhv.update(ctx)
+            observe::update(0, &mut self.bhv, ctx)
         } else {
-            match self.bhv.update(ctx) {
+            match observe::update(0, &mut self.bhv, ctx) {
                 Status::Running => {}
                 s => {
-                    self.bhv.reset(s);
+                    observe::reset(0, &mut self.bhv, s);
                     self.current += 1;
                 }
             };
@@ -144,9 +283,29 @@ impl<B: Bhv> Bhv for Repeat<B> {
     }
 
     fn reset(&mut self, _status: Status) {
-        self.bhv.reset(_status);
+        observe::reset(0, &mut self.bhv, _status);
+        self.current = 1;
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.bhv, ctx);
         self.current = 1;
     }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.current as u64]
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match *state {
+            [current] if current <= self.count as u64 => {
+                self.current = current as u32;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl<B, C> Bhv for RepeatUntil<B, C>
@@ -156,11 +315,26 @@ impl<B, C> Bhv for RepeatUntil<B, C>
 {
     type Context = B::Context;
 
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "RepeatUntil"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
-        let s = self.bhv.update(ctx);
+        let s = observe::update(0, &mut self.bhv, ctx);
         if s != Status::Running {
             self.checked_cond = false;
-            self.bhv.reset(s);
+            observe::reset(0, &mut self.bhv, s);
         }
 
         if !self.checked_cond {
@@ -175,17 +349,52 @@ impl<B, C> Bhv for RepeatUntil<B, C>
     }
 
     fn reset(&mut self, _status: Status) {
-        self.bhv.reset(_status)
+        observe::reset(0, &mut self.bhv, _status)
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.bhv, ctx);
+        self.checked_cond = false;
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.checked_cond as u64]
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match *state {
+            [checked @ (0 | 1)] => {
+                self.checked_cond = checked == 1;
+                true
+            }
+            _ => false,
+        }
     }
 }
 
 impl<B: Bhv> Bhv for RepeatUntilPass<B> {
     type Context = B::Context;
 
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "RepeatUntilPass"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.0))
+    }
+
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
-        match self.0.update(ctx) {
+        match observe::update(0, &mut self.0, ctx) {
             Status::Failure => {
-                self.0.reset(Status::Failure);
+                observe::reset(0, &mut self.0, Status::Failure);
                 Status::Running
             }
             s => s,
@@ -193,17 +402,36 @@ impl<B: Bhv> Bhv for RepeatUntilPass<B> {
     }
 
     fn reset(&mut self, _status: Status) {
-        self.0.reset(_status)
+        observe::reset(0, &mut self.0, _status)
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.0, ctx)
     }
 }
 
 impl<B: Bhv> Bhv for RepeatUntilFail<B> {
     type Context = B::Context;
 
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "RepeatUntilFail"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.0))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.0))
+    }
+
     fn update(&mut self, ctx: &mut Self::Context) -> Status {
-        match self.0.update(ctx) {
+        match observe::update(0, &mut self.0, ctx) {
             Status::Success => {
-                self.0.reset(Status::Success);
+                observe::reset(0, &mut self.0, Status::Success);
                 Status::Running
             }
             s => s,
@@ -211,6 +439,169 @@ impl<B: Bhv> Bhv for RepeatUntilFail<B> {
     }
 
     fn reset(&mut self, _status: Status) {
-        self.0.reset(_status)
+        observe::reset(0, &mut self.0, _status)
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.0, ctx)
+    }
+}
+
+impl<B, Outer, F> Bhv for WithContext<B, Outer, F>
+    where
+        B: Bhv,
+        F: Fn(&mut Outer) -> &mut B::Context,
+{
+    type Context = Outer;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "WithContext"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        observe::update(0, &mut self.bhv, (self.lens)(ctx))
+    }
+
+    #[inline]
+    fn reset(&mut self, _status: Status) {
+        observe::reset(0, &mut self.bhv, _status)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.bhv, (self.lens)(ctx))
+    }
+}
+
+impl<B: Bhv> Bhv for Label<B> {
+    type Context = B::Context;
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        self.bhv.update(ctx)
+    }
+
+    #[inline]
+    fn reset(&mut self, _status: Status) {
+        self.bhv.reset(_status)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.bhv.halt(ctx)
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        self.bhv.kind()
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.label)
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.bhv.visit_children(f)
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.bhv.visit_children_mut(f)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        self.bhv.save_state()
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        self.bhv.load_state(state)
+    }
+}
+
+impl<B: Bhv> fmt::Debug for Inv<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv> fmt::Debug for Pass<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv> fmt::Debug for Fail<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B, C> fmt::Debug for RunIf<B, C>
+    where
+        B: Bhv,
+        C: Fn(&B::Context) -> bool,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv> fmt::Debug for Repeat<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B, C> fmt::Debug for RepeatUntil<B, C>
+    where
+        B: Bhv,
+        C: Fn(&B::Context) -> bool,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv> fmt::Debug for RepeatUntilPass<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv> fmt::Debug for RepeatUntilFail<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B, Outer, F> fmt::Debug for WithContext<B, Outer, F>
+    where
+        B: Bhv,
+        F: Fn(&mut Outer) -> &mut B::Context,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv> fmt::Debug for Label<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
     }
 }
@@ -0,0 +1,107 @@
+use std::fmt::Write;
+
+use crate::{Bhv, NodeRef};
+
+/// A node of a tree, numbered in pre-order.
+struct Entry {
+    name: String,
+    parent: Option<usize>,
+}
+
+fn flatten(node: NodeRef<'_>, parent: Option<usize>, out: &mut Vec<Entry>) {
+    let id = out.len();
+    out.push(Entry {
+        name: node.name().into_owned(),
+        parent,
+    });
+
+    node.visit_children(&mut |c| flatten(c, Some(id), out));
+}
+
+/// Render the given tree as a [Graphviz](https://graphviz.org/) graph in the DOT language.
+///
+/// The nodes are labelled by their [`Bhv::name`], so leaves can be given meaningful labels
+/// through [`crate::BhvExt::label`].
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let tree = seq! {
+///     cond(|v: &i32| *v > 0).label("v > 0"),
+///     action(|v: &mut i32| *v -= 1).label("decrement"),
+/// };
+///
+/// assert_eq!(
+///     to_dot(&tree),
+///     "digraph bhv {
+///     node [shape=box];
+///     n0 [label=\"Seq\"];
+///     n1 [label=\"v > 0\"];
+///     n0 -> n1;
+///     n2 [label=\"decrement\"];
+///     n0 -> n2;
+/// }
+/// ",
+/// );
+/// ```
+pub fn to_dot<B: Bhv>(bhv: &B) -> String {
+    let mut entries = Vec::new();
+    flatten(NodeRef::new(bhv), None, &mut entries);
+
+    let mut out = String::from("digraph bhv {\n    node [shape=box];\n");
+
+    for (id, e) in entries.iter().enumerate() {
+        let label = e.name.replace('\\', "\\\\").replace('"', "\\\"");
+        // writing to a `String` never fails
+        let _ = writeln!(out, "    n{} [label=\"{}\"];", id, label);
+
+        if let Some(parent) = e.parent {
+            let _ = writeln!(out, "    n{} -> n{};", parent, id);
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Render the given tree as a [Mermaid](https://mermaid.js.org/) flowchart.
+///
+/// The nodes are labelled by their [`Bhv::name`], so leaves can be given meaningful labels
+/// through [`crate::BhvExt::label`].
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let tree = action(|v: &mut i32| *v += 1).label("increment").repeat(3);
+///
+/// assert_eq!(
+///     to_mermaid(&tree),
+///     "flowchart TD
+///     n0[\"Repeat(3)\"]
+///     n1[\"increment\"]
+///     n0 --> n1
+/// ",
+/// );
+/// ```
+pub fn to_mermaid<B: Bhv>(bhv: &B) -> String {
+    let mut entries = Vec::new();
+    flatten(NodeRef::new(bhv), None, &mut entries);
+
+    let mut out = String::from("flowchart TD\n");
+
+    for (id, e) in entries.iter().enumerate() {
+        let label = e.name.replace('"', "#quot;");
+        // writing to a `String` never fails
+        let _ = writeln!(out, "    n{}[\"{}\"]", id, label);
+
+        if let Some(parent) = e.parent {
+            let _ = writeln!(out, "    n{} --> n{}", parent, id);
+        }
+    }
+
+    out
+}
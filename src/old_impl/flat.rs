@@ -0,0 +1,620 @@
+use std::{borrow::Cow, fmt, marker::PhantomData, sync::Arc};
+
+use crate::old_impl::{
+    core::{Bhv, Status},
+    observe,
+};
+use crate::introspect::{NodeMut, NodeRef, NodeTable};
+
+/// The identifier of a node added to a [`FlatTreeBuilder`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FlatId(u32);
+
+/// The kind of a node of a flat tree, along with its parameters.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum FlatKind {
+    Seq,
+    Sel,
+    Inv,
+    Pass,
+    Fail,
+    Repeat(u32),
+    RepeatUntilPass,
+    RepeatUntilFail,
+    /// A leaf, stored separately at the given index.
+    Leaf(u32),
+}
+
+/// The definition of a node of a flat tree. The children of the node are stored in
+/// `FlatDef::edges[first..first + len]`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FlatNode {
+    kind: FlatKind,
+    first: u32,
+    len: u32,
+}
+
+/// The immutable part of a flat tree: the nodes, the edges between them and the root.
+#[derive(Clone, Debug)]
+pub(crate) struct FlatDef {
+    nodes: Vec<FlatNode>,
+    edges: Vec<u32>,
+    root: u32,
+}
+
+/// A tree stored as a single list of nodes referring to their children by index, rather than as
+/// nested boxes.
+///
+/// The structure of the tree is kept apart from its runtime state (such as the running child of a
+/// sequence), which is stored in a separate list with one value per node. Leaves are stored in a
+/// list of their own. A flat tree runs exactly like the same tree built out of [`crate::Seq`],
+/// [`crate::Sel`] and the decorators of the crate, but with fewer allocations and better locality.
+///
+/// Flat trees are created through a [`FlatTreeBuilder`]. Only sequences, selectors and the
+/// decorators added by the builder are flattened. Other nodes, such as [`crate::RunIf`],
+/// [`crate::RepeatUntil`] or the time and retry decorators, are out of scope: they can still be
+/// added as leaves, but then their children are not flattened.
+///
+/// The root of the flattened nodes is the only child of the tree. The flattened nodes can be
+/// inspected, observed and saved like the nodes they mirror, with the same kinds, names and states.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let mut b = FlatTreeBuilder::new();
+///
+/// let small = b.leaf(cond(|v: &i32| *v < 10));
+/// let double = b.leaf(action(|v: &mut i32| *v *= 2));
+/// let grow = b.seq([small, double]);
+/// let tree = b.repeat(grow, 3);
+///
+/// let mut tree = b.build(tree);
+/// let mut v = 1;
+///
+/// assert_eq!(format!("{:?}", tree), "FlatTree(4 nodes)[Repeat(3)[Seq[Cond, Action]]]");
+/// assert!(tree.execute(&mut v));
+/// assert_eq!(v, 8);
+/// ```
+pub struct FlatTree<Ctx> {
+    pub(crate) nodes: FlatNodes<Box<dyn Bhv<Context=Ctx>>>,
+}
+
+/// A builder of [`FlatTree`]s. Nodes are added bottom-up, each node receiving the identifiers of
+/// its children.
+///
+/// The leaves added to the builder are stored as `L`, which are the leaves themselves when
+/// building a [`FlatTree`], or functions creating them when building a [`crate::TreeDef`].
+pub struct FlatTreeBuilder<Ctx, L = Box<dyn Bhv<Context=Ctx>>> {
+    nodes: Vec<FlatNode>,
+    edges: Vec<u32>,
+    leaves: Vec<L>,
+    has_parent: Vec<bool>,
+    _ctx: PhantomData<fn(&mut Ctx)>,
+}
+
+/// The nodes of a flat tree: the shared definition, the runtime state of each node and the leaves.
+pub(crate) struct FlatNodes<L> {
+    def: Arc<FlatDef>,
+    state: Vec<u32>,
+    leaves: Vec<L>,
+}
+
+/// The node at index `node` of a flat tree, so that it can be run through [`observe`] like any
+/// other node.
+struct Child<'a, L> {
+    nodes: &'a mut FlatNodes<L>,
+    node: u32,
+}
+
+impl<Ctx> FlatTreeBuilder<Ctx> {
+    /// Create a builder with no nodes.
+    #[inline]
+    pub fn new() -> Self {
+        Self::empty()
+    }
+
+    /// Add a leaf node. Any node can be used as a leaf, but its children are not flattened.
+    #[inline]
+    pub fn leaf(&mut self, bhv: impl Bhv<Context=Ctx> + 'static) -> FlatId {
+        self.push_leaf(Box::new(bhv))
+    }
+
+    /// Finish the tree, with `root` as its root node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any node other than `root` was not used as a child of another node.
+    pub fn build(self, root: FlatId) -> FlatTree<Ctx> {
+        let (def, leaves) = self.finish(root);
+
+        FlatTree {
+            nodes: FlatNodes::new(Arc::new(def), leaves),
+        }
+    }
+}
+
+impl<Ctx, L> FlatTreeBuilder<Ctx, L> {
+    #[inline]
+    pub(crate) fn empty() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            leaves: Vec::new(),
+            has_parent: Vec::new(),
+            _ctx: PhantomData,
+        }
+    }
+
+    /// Add a sequence of the given nodes. See [`crate::Seq`].
+    #[inline]
+    pub fn seq(&mut self, children: impl IntoIterator<Item=FlatId>) -> FlatId {
+        self.push(FlatKind::Seq, children)
+    }
+
+    /// Add a selector of the given nodes. See [`crate::Sel`].
+    #[inline]
+    pub fn sel(&mut self, children: impl IntoIterator<Item=FlatId>) -> FlatId {
+        self.push(FlatKind::Sel, children)
+    }
+
+    /// Add a node inverting the result of `child`. See [`crate::BhvExt::inv`].
+    #[inline]
+    pub fn inv(&mut self, child: FlatId) -> FlatId {
+        self.push(FlatKind::Inv, [child])
+    }
+
+    /// Add a node that succeeds once `child` is done. See [`crate::BhvExt::pass`].
+    #[inline]
+    pub fn pass(&mut self, child: FlatId) -> FlatId {
+        self.push(FlatKind::Pass, [child])
+    }
+
+    /// Add a node that fails once `child` is done. See [`crate::BhvExt::fail`].
+    #[inline]
+    pub fn fail(&mut self, child: FlatId) -> FlatId {
+        self.push(FlatKind::Fail, [child])
+    }
+
+    /// Add a node running `child` the given number of times. See [`crate::BhvExt::repeat`].
+    #[inline]
+    pub fn repeat(&mut self, child: FlatId, count: u32) -> FlatId {
+        self.push(FlatKind::Repeat(count), [child])
+    }
+
+    /// Add a node running `child` until it succeeds. See [`crate::BhvExt::repeat_until_pass`].
+    #[inline]
+    pub fn repeat_until_pass(&mut self, child: FlatId) -> FlatId {
+        self.push(FlatKind::RepeatUntilPass, [child])
+    }
+
+    /// Add a node running `child` until it fails. See [`crate::BhvExt::repeat_until_fail`].
+    #[inline]
+    pub fn repeat_until_fail(&mut self, child: FlatId) -> FlatId {
+        self.push(FlatKind::RepeatUntilFail, [child])
+    }
+
+    /// Split the builder into the definition of the tree rooted at `root` and its leaves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any node other than `root` was not used as a child of another node.
+    pub(crate) fn finish(self, root: FlatId) -> (FlatDef, Vec<L>) {
+        let orphan = self.has_parent.iter().enumerate().any(|(i, p)| !p && i != root.0 as usize);
+        assert!(!orphan, "every node other than the root should be the child of another node");
+
+        let def = FlatDef {
+            nodes: self.nodes,
+            edges: self.edges,
+            root: root.0,
+        };
+
+        (def, self.leaves)
+    }
+
+    /// Add a leaf node stored as `leaf`.
+    pub(crate) fn push_leaf(&mut self, leaf: L) -> FlatId {
+        self.leaves.push(leaf);
+        self.push(FlatKind::Leaf(self.leaves.len() as u32 - 1), [])
+    }
+
+    /// Add a node with the given children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the children is already the child of another node.
+    fn push(&mut self, kind: FlatKind, children: impl IntoIterator<Item=FlatId>) -> FlatId {
+        let first = self.edges.len() as u32;
+
+        for c in children {
+            let has_parent = std::mem::replace(&mut self.has_parent[c.0 as usize], true);
+            assert!(!has_parent, "a node can only be the child of one node");
+            self.edges.push(c.0);
+        }
+
+        self.nodes.push(FlatNode {
+            kind,
+            first,
+            len: self.edges.len() as u32 - first,
+        });
+        self.has_parent.push(false);
+
+        FlatId(self.nodes.len() as u32 - 1)
+    }
+}
+
+impl<Ctx> Default for FlatTreeBuilder<Ctx> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FlatDef {
+    /// The state of each node before the tree runs for the first time.
+    pub(crate) fn initial_state(&self) -> Vec<u32> {
+        self.nodes
+            .iter()
+            .map(|n| match n.kind {
+                FlatKind::Repeat(_) => 1,
+                _ => 0,
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn children(&self, node: FlatNode) -> &[u32] {
+        &self.edges[node.first as usize..(node.first + node.len) as usize]
+    }
+
+    #[inline]
+    fn child(&self, node: FlatNode) -> u32 {
+        self.edges[node.first as usize]
+    }
+}
+
+impl<L: Bhv> FlatNodes<L> {
+    /// The nodes of a tree with the given definition and leaves, in their initial state.
+    pub(crate) fn new(def: Arc<FlatDef>, leaves: Vec<L>) -> Self {
+        Self {
+            state: def.initial_state(),
+            def,
+            leaves,
+        }
+    }
+
+    /// The number of nodes of the tree.
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.state.len()
+    }
+
+    /// Update the root of the tree.
+    #[inline]
+    pub(crate) fn update_root(&mut self, ctx: &mut L::Context) -> Status {
+        let root = self.def.root;
+        self.update_child(0, root, ctx)
+    }
+
+    /// Reset the root of the tree.
+    #[inline]
+    pub(crate) fn reset_root(&mut self, status: Status) {
+        let root = self.def.root;
+        self.reset_child(0, root, status)
+    }
+
+    /// Halt the root of the tree.
+    #[inline]
+    pub(crate) fn halt_root(&mut self, ctx: &mut L::Context) {
+        let root = self.def.root;
+        self.halt_child(0, root, ctx)
+    }
+
+    /// Call `f` on the root of the tree.
+    #[inline]
+    pub(crate) fn visit_root<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::in_table(self, self.def.root))
+    }
+
+    /// Call `f` on the root of the tree.
+    #[inline]
+    pub(crate) fn visit_root_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        let root = self.def.root;
+        f(NodeMut::in_table(self, root))
+    }
+
+    /// Update `node`, the child at `index` of the node being run.
+    #[inline]
+    fn update_child(&mut self, index: usize, node: u32, ctx: &mut L::Context) -> Status {
+        observe::update(index, &mut Child { nodes: self, node }, ctx)
+    }
+
+    /// Reset `node`, the child at `index` of the node being run.
+    #[inline]
+    fn reset_child(&mut self, index: usize, node: u32, status: Status) {
+        observe::reset(index, &mut Child { nodes: self, node }, status)
+    }
+
+    /// Halt `node`, the child at `index` of the node being run.
+    #[inline]
+    fn halt_child(&mut self, index: usize, node: u32, ctx: &mut L::Context) {
+        observe::halt(index, &mut Child { nodes: self, node }, ctx)
+    }
+
+    fn update(&mut self, i: u32, ctx: &mut L::Context) -> Status {
+        let node = self.def.nodes[i as usize];
+
+        match node.kind {
+            FlatKind::Seq => self.update_list(i, node, Status::Success, ctx),
+            FlatKind::Sel => self.update_list(i, node, Status::Failure, ctx),
+            FlatKind::Inv => match self.update_child(0, self.def.child(node), ctx) {
+                Status::Running => Status::Running,
+                Status::Failure => Status::Success,
+                Status::Success => Status::Failure,
+            },
+            FlatKind::Pass => match self.update_child(0, self.def.child(node), ctx) {
+                Status::Failure => Status::Success,
+                s => s,
+            },
+            FlatKind::Fail => match self.update_child(0, self.def.child(node), ctx) {
+                Status::Success => Status::Failure,
+                s => s,
+            },
+            FlatKind::Repeat(count) => {
+                let child = self.def.child(node);
+
+                if self.state[i as usize] >= count {
+                    self.update_child(0, child, ctx)
+                } else {
+                    match self.update_child(0, child, ctx) {
+                        Status::Running => {}
+                        s => {
+                            self.reset_child(0, child, s);
+                            self.state[i as usize] += 1;
+                        }
+                    }
+
+                    Status::Running
+                }
+            }
+            FlatKind::RepeatUntilPass => {
+                let child = self.def.child(node);
+
+                match self.update_child(0, child, ctx) {
+                    Status::Failure => {
+                        self.reset_child(0, child, Status::Failure);
+                        Status::Running
+                    }
+                    s => s,
+                }
+            }
+            FlatKind::RepeatUntilFail => {
+                let child = self.def.child(node);
+
+                match self.update_child(0, child, ctx) {
+                    Status::Success => {
+                        self.reset_child(0, child, Status::Success);
+                        Status::Running
+                    }
+                    s => s,
+                }
+            }
+            FlatKind::Leaf(l) => self.leaves[l as usize].update(ctx),
+        }
+    }
+
+    /// Update a sequence or selector, which completes once a child does not return `policy`.
+    fn update_list(&mut self, i: u32, node: FlatNode, policy: Status, ctx: &mut L::Context) -> Status {
+        while let Some(c) = self.def.children(node).get(self.state[i as usize] as usize).copied() {
+            let s = self.update_child(self.state[i as usize] as usize, c, ctx);
+
+            if s == policy {
+                self.state[i as usize] += 1;
+            } else {
+                if s != Status::Running {
+                    self.reset(i, s);
+                }
+
+                return s;
+            }
+        }
+
+        self.reset(i, policy);
+        policy
+    }
+
+    fn reset(&mut self, i: u32, status: Status) {
+        let node = self.def.nodes[i as usize];
+
+        match node.kind {
+            FlatKind::Seq | FlatKind::Sel => {
+                let policy = if let FlatKind::Seq = node.kind { Status::Success } else { Status::Failure };
+                let count = self.state[i as usize].min(node.len) as usize;
+
+                for k in 0..count {
+                    let c = self.def.children(node)[k];
+                    self.reset_child(k, c, policy);
+                }
+
+                self.state[i as usize] = 0;
+            }
+            FlatKind::Repeat(_) => {
+                self.reset_child(0, self.def.child(node), status);
+                self.state[i as usize] = 1;
+            }
+            FlatKind::Leaf(l) => self.leaves[l as usize].reset(status),
+            _ => self.reset_child(0, self.def.child(node), status),
+        }
+    }
+
+    fn halt(&mut self, i: u32, ctx: &mut L::Context) {
+        let node = self.def.nodes[i as usize];
+
+        match node.kind {
+            FlatKind::Seq | FlatKind::Sel => {
+                let policy = if let FlatKind::Seq = node.kind { Status::Success } else { Status::Failure };
+                let current = self.state[i as usize] as usize;
+
+                if let Some(&c) = self.def.children(node).get(current) {
+                    self.halt_child(current, c, ctx);
+                }
+
+                self.reset(i, policy);
+            }
+            FlatKind::Repeat(_) => {
+                self.halt_child(0, self.def.child(node), ctx);
+                self.state[i as usize] = 1;
+            }
+            FlatKind::Leaf(l) => self.leaves[l as usize].halt(ctx),
+            _ => self.halt_child(0, self.def.child(node), ctx),
+        }
+    }
+}
+
+impl<L: Bhv> NodeTable for FlatNodes<L> {
+    fn kind(&self, node: u32) -> &'static str {
+        match self.def.nodes[node as usize].kind {
+            FlatKind::Seq => "Seq",
+            FlatKind::Sel => "Sel",
+            FlatKind::Inv => "Inv",
+            FlatKind::Pass => "Pass",
+            FlatKind::Fail => "Fail",
+            FlatKind::Repeat(_) => "Repeat",
+            FlatKind::RepeatUntilPass => "RepeatUntilPass",
+            FlatKind::RepeatUntilFail => "RepeatUntilFail",
+            FlatKind::Leaf(l) => self.leaves[l as usize].kind(),
+        }
+    }
+
+    fn name(&self, node: u32) -> Cow<'_, str> {
+        match self.def.nodes[node as usize].kind {
+            FlatKind::Repeat(count) => Cow::Owned(format!("Repeat({})", count)),
+            FlatKind::Leaf(l) => self.leaves[l as usize].name(),
+            _ => Cow::Borrowed(NodeTable::kind(self, node)),
+        }
+    }
+
+    fn visit_children<'a>(&'a self, node: u32, f: &mut dyn FnMut(NodeRef<'a>)) {
+        let node = self.def.nodes[node as usize];
+
+        match node.kind {
+            FlatKind::Leaf(l) => self.leaves[l as usize].visit_children(f),
+            _ => self.def.children(node).iter().for_each(|&c| f(NodeRef::in_table(self, c))),
+        }
+    }
+
+    fn visit_children_mut(&mut self, node: u32, f: &mut dyn FnMut(NodeMut<'_>)) {
+        let node = self.def.nodes[node as usize];
+
+        match node.kind {
+            FlatKind::Leaf(l) => self.leaves[l as usize].visit_children_mut(f),
+            _ => {
+                for k in 0..node.len as usize {
+                    let c = self.def.children(node)[k];
+                    f(NodeMut::in_table(self, c));
+                }
+            }
+        }
+    }
+
+    fn save_state(&self, node: u32) -> Vec<u64> {
+        match self.def.nodes[node as usize].kind {
+            FlatKind::Seq | FlatKind::Sel | FlatKind::Repeat(_) => vec![self.state[node as usize] as u64],
+            FlatKind::Leaf(l) => self.leaves[l as usize].save_state(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn load_state(&mut self, node: u32, state: &[u64]) -> bool {
+        let n = self.def.nodes[node as usize];
+
+        let valid = match (n.kind, state) {
+            (FlatKind::Leaf(l), _) => return self.leaves[l as usize].load_state(state),
+            (FlatKind::Seq | FlatKind::Sel, &[s]) => s < n.len as u64,
+            (FlatKind::Repeat(count), &[s]) => s <= count as u64,
+            (FlatKind::Seq | FlatKind::Sel | FlatKind::Repeat(_), _) => false,
+            (_, s) => return s.is_empty(),
+        };
+
+        if valid {
+            self.state[node as usize] = state[0] as u32;
+        }
+
+        valid
+    }
+}
+
+impl<L: Bhv> Bhv for Child<'_, L> {
+    type Context = L::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        NodeTable::kind(self.nodes, self.node)
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        NodeTable::name(self.nodes, self.node)
+    }
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        self.nodes.update(self.node, ctx)
+    }
+
+    #[inline]
+    fn reset(&mut self, status: Status) {
+        self.nodes.reset(self.node, status)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.nodes.halt(self.node, ctx)
+    }
+}
+
+impl<Ctx> Bhv for FlatTree<Ctx> {
+    type Context = Ctx;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "FlatTree"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("FlatTree({} nodes)", self.nodes.len()))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.nodes.visit_root(f)
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.nodes.visit_root_mut(f)
+    }
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        self.nodes.update_root(ctx)
+    }
+
+    #[inline]
+    fn reset(&mut self, status: Status) {
+        self.nodes.reset_root(status)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.nodes.halt_root(ctx)
+    }
+}
+
+impl<Ctx> fmt::Debug for FlatTree<Ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
@@ -0,0 +1,155 @@
+use std::{borrow::Cow, sync::Arc};
+
+use crate::old_impl::{
+    core::{Bhv, Status},
+    flat::{FlatDef, FlatId, FlatNodes, FlatTreeBuilder},
+};
+use crate::introspect::{NodeMut, NodeRef};
+
+/// A leaf of a [`TreeInstance`].
+pub type InstanceLeaf<Ctx> = Box<dyn Bhv<Context=Ctx> + Send>;
+
+/// A function creating a leaf of a [`TreeDef`] for each new [`TreeInstance`].
+pub type LeafFactory<Ctx> = Box<dyn Fn() -> InstanceLeaf<Ctx> + Send + Sync>;
+
+/// A builder of [`TreeDef`]s, created by [`TreeDef::builder`].
+pub type TreeDefBuilder<Ctx> = FlatTreeBuilder<Ctx, LeafFactory<Ctx>>;
+
+/// The immutable definition of a tree, which can be shared between any number of agents.
+///
+/// A definition stores the shape of the tree along with functions creating its leaves. Cloning a
+/// definition is cheap, as the definition is shared behind an [`Arc`]. Each agent then runs its own
+/// [`TreeInstance`], created by [`TreeDef::instantiate`], which only holds the runtime state of the
+/// tree and the leaves.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let mut b = TreeDef::builder();
+///
+/// let far = b.leaf(|| cond(|pos: &i32| *pos < 3));
+/// let step = b.leaf(|| action(|pos: &mut i32| *pos += 1));
+/// let walk = b.seq([far, step]);
+/// let walk = b.repeat_until_fail(walk);
+/// let walk = b.pass(walk);
+///
+/// let def = b.build(walk);
+///
+/// let mut npcs: Vec<_> = (0..500).map(|_| (Runner::new(def.instantiate()), 0)).collect();
+///
+/// for _frame in 0..4 {
+///     for (tree, pos) in &mut npcs {
+///         tree.tick(pos);
+///     }
+/// }
+///
+/// assert!(npcs.iter().all(|(tree, pos)| *pos == 3 && tree.last_status() == Some(Status::Success)));
+/// ```
+pub struct TreeDef<Ctx>(Arc<DefInner<Ctx>>);
+
+struct DefInner<Ctx> {
+    def: Arc<FlatDef>,
+    leaves: Vec<LeafFactory<Ctx>>,
+}
+
+/// A tree running on its own, created out of a shared [`TreeDef`].
+///
+/// An instance stores the runtime state of each node of the tree, such as the running child of a
+/// sequence or the number of completed runs of a repeating node, along with its own leaves. It runs
+/// exactly like the [`crate::FlatTree`] built out of the same nodes. As the leaves of a definition
+/// are `Send`, so are its instances, which can be moved to other threads.
+pub struct TreeInstance<Ctx> {
+    def: TreeDef<Ctx>,
+    nodes: FlatNodes<InstanceLeaf<Ctx>>,
+}
+
+impl<Ctx> TreeDef<Ctx> {
+    /// Create a builder of a tree definition.
+    #[inline]
+    pub fn builder() -> TreeDefBuilder<Ctx> {
+        FlatTreeBuilder::empty()
+    }
+
+    /// Create a new instance of the tree, in its initial state.
+    pub fn instantiate(&self) -> TreeInstance<Ctx> {
+        TreeInstance {
+            def: self.clone(),
+            nodes: FlatNodes::new(Arc::clone(&self.0.def), self.0.leaves.iter().map(|f| f()).collect()),
+        }
+    }
+}
+
+impl<Ctx> Clone for TreeDef<Ctx> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<Ctx> TreeDefBuilder<Ctx> {
+    /// Add a leaf node, created by calling `f` once for every instance of the tree.
+    #[inline]
+    pub fn leaf<B>(&mut self, f: impl Fn() -> B + Send + Sync + 'static) -> FlatId
+        where B: Bhv<Context=Ctx> + Send + 'static {
+        self.push_leaf(Box::new(move || Box::new(f())))
+    }
+
+    /// Finish the definition, with `root` as its root node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any node other than `root` was not used as a child of another node.
+    pub fn build(self, root: FlatId) -> TreeDef<Ctx> {
+        let (def, leaves) = self.finish(root);
+        TreeDef(Arc::new(DefInner { def: Arc::new(def), leaves }))
+    }
+}
+
+impl<Ctx> TreeInstance<Ctx> {
+    /// The definition this instance was created from.
+    #[inline]
+    pub fn def(&self) -> &TreeDef<Ctx> {
+        &self.def
+    }
+}
+
+impl<Ctx> Bhv for TreeInstance<Ctx> {
+    type Context = Ctx;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "TreeInstance"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("TreeInstance({} nodes)", self.nodes.len()))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.nodes.visit_root(f)
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.nodes.visit_root_mut(f)
+    }
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        self.nodes.update_root(ctx)
+    }
+
+    #[inline]
+    fn reset(&mut self, status: Status) {
+        self.nodes.reset_root(status)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.nodes.halt_root(ctx)
+    }
+}
@@ -0,0 +1,625 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt,
+    time::Duration,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    wait_ticks, Bhv, BhvExt, Parallel, ReactiveSel, ReactiveSeq, Sel, Seq, WhenAll, WhenAny,
+};
+
+/// A boxed behavior, as built by a [`NodeRegistry`].
+pub type BoxedBhv<Ctx> = Box<dyn Bhv<Context=Ctx>>;
+
+type Constructor<Ctx> = Box<dyn Fn(&Params, Vec<BoxedBhv<Ctx>>) -> Result<BoxedBhv<Ctx>, LoadError>>;
+
+/// A description of a node of a tree: the name it is registered with in a [`NodeRegistry`],
+/// its parameters and the descriptions of its children.
+///
+/// With the `serde` feature enabled, descriptions can be (de)serialized. The parameters and
+/// children can be omitted when empty, so the tree below can be written in JSON as
+///
+/// ```json
+/// {
+///     "node": "seq",
+///     "children": [
+///         { "node": "is_hungry" },
+///         { "node": "repeat", "params": { "count": 3 }, "children": [{ "node": "eat" }] }
+///     ]
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeDesc {
+    /// The name of the node.
+    pub node: String,
+    /// The parameters of the node, by name.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    pub params: BTreeMap<String, Param>,
+    /// The descriptions of the children of the node.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub children: Vec<NodeDesc>,
+}
+
+/// The value of a parameter of a [`NodeDesc`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
+pub enum Param {
+    /// A boolean, such as `true`.
+    Bool(bool),
+    /// A whole number, such as `3`. Integers are also accepted where a float is expected.
+    Int(i64),
+    /// A number with a fractional part, such as `0.5`.
+    Float(f64),
+    /// A string, such as `"attack"`.
+    Str(String),
+}
+
+/// The parameters passed to a node constructor of a [`NodeRegistry`].
+pub struct Params<'a> {
+    node: &'a str,
+    path: &'a [usize],
+    values: &'a BTreeMap<String, Param>,
+}
+
+/// Types that can be read from a [`Param`].
+pub trait FromParam: Sized {
+    /// A short description of the expected parameter, used for error messages.
+    const EXPECTED: &'static str;
+
+    /// Convert the parameter, if it holds a value of the right type.
+    fn from_param(param: &Param) -> Option<Self>;
+}
+
+/// The number of children a node expects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arity {
+    /// The given number of children, no more and no less.
+    Exactly(usize),
+    /// The given number of children or more.
+    AtLeast(usize),
+}
+
+/// An error that occurred while building a tree out of a description.
+///
+/// Errors about a node carry the path of the node, that is the indices of the children to go
+/// through to reach the node, starting from the root. The path of the root is empty.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadError {
+    /// There is no node registered with the given name.
+    UnknownNode {
+        /// The name of the node.
+        node: String,
+        /// The path of the node.
+        path: Vec<usize>,
+    },
+    /// The node was given the wrong number of children.
+    ChildCount {
+        /// The name of the node.
+        node: String,
+        /// The path of the node.
+        path: Vec<usize>,
+        /// The number of children the node expects.
+        expected: Arity,
+        /// The number of children the node was given.
+        found: usize,
+    },
+    /// A parameter of the node is missing or invalid.
+    BadParam {
+        /// The name of the node.
+        node: String,
+        /// The path of the node.
+        path: Vec<usize>,
+        /// The name of the parameter.
+        param: String,
+        /// Why the parameter was rejected.
+        reason: String,
+    },
+    /// The description could not be parsed.
+    Parse(String),
+}
+
+/// A collection of named constructors used to build trees out of [`NodeDesc`]s, such as trees
+/// loaded from data files.
+///
+/// The composite and decorator nodes of the crate are registered by default, along with
+/// `wait_ticks`. Their parameters are:
+///
+/// - `repeat`: `count`
+/// - `retry`: `attempts`
+/// - `parallel`: `success` (defaults to the number of children) and `failure` (defaults to 1), both
+///   between 1 and the number of children
+/// - `timeout`, `delay`, `cooldown`: `secs`
+/// - `throttle`: `per_second`, greater than 0
+/// - `wait_ticks`: `count`
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let mut registry = NodeRegistry::new();
+///
+/// registry.register_leaf("add", |p| {
+///     let amount: i32 = p.get("amount")?;
+///     Ok(action(move |v| *v += amount))
+/// });
+///
+/// let desc = NodeDesc::new("seq")
+///     .child(NodeDesc::new("add").param("amount", 2))
+///     .child(
+///         NodeDesc::new("repeat")
+///             .param("count", 3)
+///             .child(NodeDesc::new("add").param("amount", 10)),
+///     );
+///
+/// let tree = registry.build(&desc).unwrap();
+///
+/// let mut ctx = 0;
+/// tree.execute(&mut ctx);
+///
+/// assert_eq!(ctx, 32);
+///
+/// let bad = NodeDesc::new("seq")
+///     .child(NodeDesc::new("add").param("amount", 1))
+///     .child(NodeDesc::new("inv"));
+///
+/// assert_eq!(
+///     registry.build(&bad).err(),
+///     Some(LoadError::ChildCount {
+///         node: "inv".to_string(),
+///         path: vec![1],
+///         expected: Arity::Exactly(1),
+///         found: 0,
+///     }),
+/// );
+/// ```
+pub struct NodeRegistry<Ctx> {
+    constructors: HashMap<String, Constructor<Ctx>>,
+}
+
+impl NodeDesc {
+    /// Create a description of the node registered as `node`, without parameters or children.
+    #[inline]
+    pub fn new(node: impl Into<String>) -> Self {
+        Self {
+            node: node.into(),
+            params: BTreeMap::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Add a parameter to the description.
+    #[inline]
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<Param>) -> Self {
+        self.params.insert(name.into(), value.into());
+        self
+    }
+
+    /// Add a child to the description.
+    #[inline]
+    pub fn child(mut self, child: NodeDesc) -> Self {
+        self.children.push(child);
+        self
+    }
+}
+
+impl From<bool> for Param {
+    #[inline]
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl From<i64> for Param {
+    #[inline]
+    fn from(v: i64) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<i32> for Param {
+    #[inline]
+    fn from(v: i32) -> Self {
+        Self::Int(v.into())
+    }
+}
+
+impl From<u32> for Param {
+    #[inline]
+    fn from(v: u32) -> Self {
+        Self::Int(v.into())
+    }
+}
+
+impl From<f64> for Param {
+    #[inline]
+    fn from(v: f64) -> Self {
+        Self::Float(v)
+    }
+}
+
+impl From<&str> for Param {
+    #[inline]
+    fn from(v: &str) -> Self {
+        Self::Str(v.to_string())
+    }
+}
+
+impl From<String> for Param {
+    #[inline]
+    fn from(v: String) -> Self {
+        Self::Str(v)
+    }
+}
+
+impl FromParam for bool {
+    const EXPECTED: &'static str = "a boolean";
+
+    #[inline]
+    fn from_param(param: &Param) -> Option<Self> {
+        match param {
+            Param::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_from_param_int {
+    ($($t:ty => $e:expr),+$(,)?) => {
+        $(
+            impl FromParam for $t {
+                const EXPECTED: &'static str = $e;
+
+                #[inline]
+                fn from_param(param: &Param) -> Option<Self> {
+                    match param {
+                        Param::Int(v) => (*v).try_into().ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_from_param_int! {
+    i32 => "an integer",
+    i64 => "an integer",
+    u32 => "a non-negative integer",
+    u64 => "a non-negative integer",
+    usize => "a non-negative integer",
+}
+
+impl FromParam for f64 {
+    const EXPECTED: &'static str = "a number";
+
+    #[inline]
+    fn from_param(param: &Param) -> Option<Self> {
+        match param {
+            Param::Int(v) => Some(*v as f64),
+            Param::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl FromParam for String {
+    const EXPECTED: &'static str = "a string";
+
+    #[inline]
+    fn from_param(param: &Param) -> Option<Self> {
+        match param {
+            Param::Str(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromParam for Duration {
+    const EXPECTED: &'static str = "a non-negative number of seconds";
+
+    #[inline]
+    fn from_param(param: &Param) -> Option<Self> {
+        f64::from_param(param).and_then(|v| Duration::try_from_secs_f64(v).ok())
+    }
+}
+
+impl<'a> Params<'a> {
+    /// The name of the node being built.
+    #[inline]
+    pub fn node(&self) -> &'a str {
+        self.node
+    }
+
+    /// The path of the node being built, as found in [`LoadError`]s.
+    #[inline]
+    pub fn path(&self) -> &'a [usize] {
+        self.path
+    }
+
+    /// Get the parameter `name`, failing if it is missing or is not of type `T`.
+    pub fn get<T: FromParam>(&self, name: &str) -> Result<T, LoadError> {
+        match self.values.get(name) {
+            Some(v) => T::from_param(v).ok_or_else(|| self.bad_param(name, format!("expected {}", T::EXPECTED))),
+            None => Err(self.bad_param(name, "missing parameter".to_string())),
+        }
+    }
+
+    /// Get the parameter `name`, or `default` if it is missing.
+    /// Fails if the parameter is present but is not of type `T`.
+    pub fn get_or<T: FromParam>(&self, name: &str, default: T) -> Result<T, LoadError> {
+        if self.values.contains_key(name) {
+            self.get(name)
+        } else {
+            Ok(default)
+        }
+    }
+
+    fn bad_param(&self, name: &str, reason: String) -> LoadError {
+        LoadError::BadParam {
+            node: self.node.to_string(),
+            path: self.path.to_vec(),
+            param: name.to_string(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exactly(1) => write!(f, "exactly 1 child"),
+            Self::Exactly(n) => write!(f, "exactly {} children", n),
+            Self::AtLeast(1) => write!(f, "at least 1 child"),
+            Self::AtLeast(n) => write!(f, "at least {} children", n),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownNode { node, path } => write!(f, "unknown node `{}` at {:?}", node, path),
+            Self::ChildCount { node, path, expected, found } => {
+                write!(f, "node `{}` at {:?} expects {}, found {}", node, path, expected, found)
+            }
+            Self::BadParam { node, path, param, reason } => {
+                write!(f, "bad parameter `{}` of node `{}` at {:?}: {}", param, node, path, reason)
+            }
+            Self::Parse(e) => write!(f, "could not parse the tree: {}", e),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+/// Check that a node has exactly one child and return it.
+fn single_child<Ctx>(p: &Params, mut nodes: Vec<BoxedBhv<Ctx>>) -> Result<BoxedBhv<Ctx>, LoadError> {
+    match (nodes.pop(), nodes.len()) {
+        (Some(n), 0) => Ok(n),
+        (n, len) => Err(LoadError::ChildCount {
+            node: p.node().to_string(),
+            path: p.path().to_vec(),
+            expected: Arity::Exactly(1),
+            found: len + n.is_some() as usize,
+        }),
+    }
+}
+
+/// Check that a node has at least one child.
+fn some_children<Ctx>(p: &Params, nodes: Vec<BoxedBhv<Ctx>>) -> Result<Vec<BoxedBhv<Ctx>>, LoadError> {
+    if nodes.is_empty() {
+        Err(LoadError::ChildCount {
+            node: p.node().to_string(),
+            path: p.path().to_vec(),
+            expected: Arity::AtLeast(1),
+            found: 0,
+        })
+    } else {
+        Ok(nodes)
+    }
+}
+
+impl<Ctx: 'static> NodeRegistry<Ctx> {
+    /// Create a registry with the nodes provided by the crate.
+    pub fn new() -> Self {
+        let mut r = Self::empty();
+
+        r.register("seq", |p, c| Ok(Box::new(Seq::with_nodes(some_children(p, c)?))));
+        r.register("sel", |p, c| Ok(Box::new(Sel::with_nodes(some_children(p, c)?))));
+        r.register("reactive_seq", |p, c| Ok(Box::new(ReactiveSeq::with_nodes(some_children(p, c)?))));
+        r.register("reactive_sel", |p, c| Ok(Box::new(ReactiveSel::with_nodes(some_children(p, c)?))));
+        r.register("when_any", |p, c| Ok(Box::new(WhenAny::new(some_children(p, c)?.into_boxed_slice()))));
+        r.register("when_all", |p, c| Ok(Box::new(WhenAll::new(some_children(p, c)?.into_boxed_slice()))));
+        r.register("parallel", |p, c| {
+            let c = some_children(p, c)?;
+            let success = p.get_or("success", c.len())?;
+            let failure = p.get_or("failure", 1)?;
+
+            for (name, value) in [("success", success), ("failure", failure)] {
+                if !(1..=c.len()).contains(&value) {
+                    return Err(p.bad_param(name, format!("{} is not between 1 and {}", value, c.len())));
+                }
+            }
+
+            Ok(Box::new(Parallel::new(c.into_boxed_slice(), success, failure)))
+        });
+
+        r.register("inv", |p, c| Ok(Box::new(single_child(p, c)?.inv())));
+        r.register("pass", |p, c| Ok(Box::new(single_child(p, c)?.pass())));
+        r.register("fail", |p, c| Ok(Box::new(single_child(p, c)?.fail())));
+        r.register("repeat", |p, c| Ok(Box::new(single_child(p, c)?.repeat(p.get("count")?))));
+        r.register("repeat_until_pass", |p, c| Ok(Box::new(single_child(p, c)?.repeat_until_pass())));
+        r.register("repeat_until_fail", |p, c| Ok(Box::new(single_child(p, c)?.repeat_until_fail())));
+        r.register("retry", |p, c| Ok(Box::new(single_child(p, c)?.retry(p.get("attempts")?))));
+        r.register("timeout", |p, c| Ok(Box::new(single_child(p, c)?.timeout(p.get("secs")?))));
+        r.register("delay", |p, c| Ok(Box::new(single_child(p, c)?.delay(p.get("secs")?))));
+        r.register("cooldown", |p, c| Ok(Box::new(single_child(p, c)?.cooldown(p.get("secs")?))));
+        r.register("throttle", |p, c| {
+            let child = single_child(p, c)?;
+
+            match p.get("per_second")? {
+                0 => Err(p.bad_param("per_second", "0 is not greater than 0".to_string())),
+                per_second => Ok(Box::new(child.throttle(per_second))),
+            }
+        });
+
+        r.register_leaf("wait_ticks", |p| Ok(wait_ticks(p.get("count")?)));
+
+        r
+    }
+
+    /// Create a registry without any nodes.
+    #[inline]
+    pub fn empty() -> Self {
+        Self {
+            constructors: HashMap::new(),
+        }
+    }
+
+    /// Register a constructor for nodes named `name`, replacing any previous one.
+    /// The constructor receives the parameters of the node and its children, already built.
+    pub fn register<F>(&mut self, name: impl Into<String>, f: F)
+        where
+            F: Fn(&Params, Vec<BoxedBhv<Ctx>>) -> Result<BoxedBhv<Ctx>, LoadError> + 'static,
+    {
+        self.constructors.insert(name.into(), Box::new(f));
+    }
+
+    /// Register a constructor for leaf nodes named `name`, replacing any previous one.
+    /// Descriptions of these nodes that have children are rejected.
+    pub fn register_leaf<B, F>(&mut self, name: impl Into<String>, f: F)
+        where
+            B: Bhv<Context=Ctx> + 'static,
+            F: Fn(&Params) -> Result<B, LoadError> + 'static,
+    {
+        self.register(name, move |p, c| {
+            if c.is_empty() {
+                Ok(Box::new(f(p)?))
+            } else {
+                Err(LoadError::ChildCount {
+                    node: p.node().to_string(),
+                    path: p.path().to_vec(),
+                    expected: Arity::Exactly(0),
+                    found: c.len(),
+                })
+            }
+        });
+    }
+
+    /// Check whether a constructor is registered as `name`.
+    #[inline]
+    pub fn contains(&self, name: &str) -> bool {
+        self.constructors.contains_key(name)
+    }
+
+    /// Build a tree out of its description.
+    #[inline]
+    pub fn build(&self, desc: &NodeDesc) -> Result<BoxedBhv<Ctx>, LoadError> {
+        self.build_at(desc, &mut Vec::new())
+    }
+
+    /// Build the node found at `path` out of its description.
+    fn build_at(&self, desc: &NodeDesc, path: &mut Vec<usize>) -> Result<BoxedBhv<Ctx>, LoadError> {
+        let ctor = self
+            .constructors
+            .get(&desc.node)
+            .ok_or_else(|| LoadError::UnknownNode {
+                node: desc.node.clone(),
+                path: path.clone(),
+            })?;
+
+        let mut children = Vec::with_capacity(desc.children.len());
+
+        for (i, c) in desc.children.iter().enumerate() {
+            path.push(i);
+            children.push(self.build_at(c, path)?);
+            path.pop();
+        }
+
+        ctor(
+            &Params {
+                node: &desc.node,
+                path,
+                values: &desc.params,
+            },
+            children,
+        )
+    }
+
+    /// Build a tree out of its description, written in JSON.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// let mut registry = NodeRegistry::new();
+    /// registry.register_leaf("inc", |_| Ok(action(|v| *v += 1)));
+    ///
+    /// let tree = registry.load_json(r#"{
+    ///     "node": "repeat",
+    ///     "params": { "count": 4 },
+    ///     "children": [{ "node": "inc" }]
+    /// }"#).unwrap();
+    ///
+    /// let mut ctx = 0;
+    /// tree.execute(&mut ctx);
+    ///
+    /// assert_eq!(ctx, 4);
+    ///
+    /// assert_eq!(
+    ///     registry.load_json(r#"{ "node": "jump" }"#).err(),
+    ///     Some(LoadError::UnknownNode { node: "jump".to_string(), path: vec![] }),
+    /// );
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn load_json(&self, src: &str) -> Result<BoxedBhv<Ctx>, LoadError> {
+        let desc = serde_json::from_str(src).map_err(|e| LoadError::Parse(e.to_string()))?;
+        self.build(&desc)
+    }
+
+    /// Build a tree out of its description, written in RON.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// let mut registry = NodeRegistry::new();
+    /// registry.register_leaf("inc", |_| Ok(action(|v| *v += 1)));
+    ///
+    /// let tree = registry.load_ron(r#"(
+    ///     node: "retry",
+    ///     params: { "attempts": 3 },
+    ///     children: [(node: "inc")],
+    /// )"#).unwrap();
+    ///
+    /// let mut ctx = 0;
+    /// tree.execute(&mut ctx);
+    ///
+    /// assert_eq!(ctx, 1);
+    ///
+    /// assert!(matches!(
+    ///     registry.load_ron(r#"(node: "retry", params: { "attempts": "many" }, children: [(node: "inc")])"#),
+    ///     Err(LoadError::BadParam { .. }),
+    /// ));
+    /// ```
+    #[cfg(feature = "ron")]
+    pub fn load_ron(&self, src: &str) -> Result<BoxedBhv<Ctx>, LoadError> {
+        let desc = ron::from_str(src).map_err(|e| LoadError::Parse(e.to_string()))?;
+        self.build(&desc)
+    }
+}
+
+impl<Ctx: 'static> Default for NodeRegistry<Ctx> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,13 +1,51 @@
 pub use self::adapt::*;
 pub use self::async_composite::*;
 pub use self::bhv_ext::BhvExt;
+pub use self::blackboard::*;
+#[cfg(feature = "bevy")]
+pub use self::bevy::*;
 pub use self::composite::*;
 pub use self::core::*;
+pub use self::debug::*;
 pub use self::decor::*;
+pub use self::export::*;
+pub use self::flat::*;
+pub use self::instance::*;
+pub use self::loader::*;
+pub use self::observe::{NodeId, NodeInfo, Observed, TreeObserver};
+#[cfg(feature = "threads")]
+pub use self::par::*;
+pub use self::record::*;
+pub use self::retry::*;
+pub use self::runner::*;
+pub use self::snapshot::*;
+pub use self::time::*;
+pub use self::tuple::*;
+#[cfg(feature = "tracing")]
+pub use self::tracing::TracingObserver;
 
 mod adapt;
+#[cfg(feature = "bevy")]
+mod bevy;
 mod bhv_ext;
+mod blackboard;
 mod composite;
 mod core;
+mod debug;
 mod decor;
-mod async_composite;
\ No newline at end of file
+mod export;
+mod flat;
+mod instance;
+mod loader;
+mod async_composite;
+mod observe;
+#[cfg(feature = "threads")]
+mod par;
+mod record;
+mod retry;
+mod runner;
+mod snapshot;
+mod time;
+mod tuple;
+#[cfg(feature = "tracing")]
+mod tracing;
\ No newline at end of file
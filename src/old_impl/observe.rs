@@ -0,0 +1,394 @@
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    collections::HashMap,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{Bhv, NodeMut, NodeRef, Status};
+
+/// An identifier of a node, unique within an observed tree.
+///
+/// Nodes are numbered in pre-order as reported by [`Bhv::visit_children`], starting from 0
+/// for the root, so the identifiers stay the same between runs of the same tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(pub usize);
+
+/// Information about a node passed to a [`TreeObserver`].
+#[derive(Clone, Copy, Debug)]
+pub struct NodeInfo<'a> {
+    /// The identifier of the node.
+    pub id: NodeId,
+    /// The indices of the children to go through to reach the node, starting from the root.
+    /// The path of the root is empty.
+    pub path: &'a [usize],
+    /// The kind of the node. See [`Bhv::kind`].
+    pub kind: &'static str,
+    /// The name of the node. See [`Bhv::name`].
+    pub name: &'a str,
+}
+
+/// A trait for types that are notified about the lifecycle of the nodes of a tree, such as
+/// loggers and debuggers. All the methods default to nothing.
+///
+/// Observers are attached to a tree through [`crate::BhvExt::observed`]. While an observed tree is
+/// running, the nodes of the crate report the transitions of their children to the observer.
+/// Nodes that are not reached through the nodes of the crate are not reported. Observers can run
+/// other trees from their methods, whose nodes are not reported either.
+pub trait TreeObserver {
+    /// Called before a node is updated.
+    #[inline]
+    fn on_enter(&mut self, _node: &NodeInfo<'_>) {}
+
+    /// Called after a node is updated, with the status it returned.
+    #[inline]
+    fn on_status(&mut self, _node: &NodeInfo<'_>, _status: Status) {}
+
+    /// Called before a node is reset.
+    #[inline]
+    fn on_reset(&mut self, _node: &NodeInfo<'_>, _status: Status) {}
+
+    /// Called before a node is halted.
+    #[inline]
+    fn on_halt(&mut self, _node: &NodeInfo<'_>) {}
+}
+
+/// A decorator that reports the lifecycle of the given node and its descendants to a [`TreeObserver`].
+pub struct Observed<B: Bhv, O: TreeObserver + 'static> {
+    pub(crate) bhv: B,
+    pub(crate) observer: Rc<RefCell<O>>,
+    pub(crate) ids: HashMap<Vec<usize>, usize>,
+}
+
+/// The state of the observed tree that is currently running.
+struct Active {
+    observer: Rc<RefCell<dyn TreeObserver>>,
+    ids: HashMap<Vec<usize>, usize>,
+    path: Vec<usize>,
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<Active>> = const { RefCell::new(None) };
+}
+
+/// The number of observed trees running on any thread. While this is zero, children are run
+/// directly, without looking up the active tree of the current thread.
+static OBSERVED: AtomicUsize = AtomicUsize::new(0);
+
+/// Puts the given tree back as the active one of the current thread when dropped, even if the
+/// code in between panics.
+struct Restore {
+    active: Option<Active>,
+    observed: bool,
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let active = self.active.take();
+        ACTIVE.with(|a| *a.borrow_mut() = active);
+
+        if self.observed {
+            OBSERVED.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Check whether an observed tree might be running, without accessing thread-local storage.
+#[inline(always)]
+fn maybe_observed() -> bool {
+    OBSERVED.load(Ordering::Relaxed) != 0
+}
+
+impl<O: TreeObserver> TreeObserver for Rc<RefCell<O>> {
+    #[inline]
+    fn on_enter(&mut self, node: &NodeInfo<'_>) {
+        self.borrow_mut().on_enter(node)
+    }
+
+    #[inline]
+    fn on_status(&mut self, node: &NodeInfo<'_>, status: Status) {
+        self.borrow_mut().on_status(node, status)
+    }
+
+    #[inline]
+    fn on_reset(&mut self, node: &NodeInfo<'_>, status: Status) {
+        self.borrow_mut().on_reset(node, status)
+    }
+
+    #[inline]
+    fn on_halt(&mut self, node: &NodeInfo<'_>) {
+        self.borrow_mut().on_halt(node)
+    }
+}
+
+/// Number the nodes of the tree in pre-order, by path.
+fn number(node: NodeRef<'_>, path: &mut Vec<usize>, ids: &mut HashMap<Vec<usize>, usize>) {
+    ids.insert(path.clone(), ids.len());
+
+    for (i, c) in node.children().into_iter().enumerate() {
+        path.push(i);
+        number(c, path, ids);
+        path.pop();
+    }
+}
+
+/// Report an event about the node at the current path of the active tree, if any.
+///
+/// The active tree is taken out of thread-local storage while the observer runs, so that the
+/// observer can run other trees without them being reported.
+fn report<B: Bhv>(bhv: &B, f: impl FnOnce(&mut dyn TreeObserver, &NodeInfo<'_>)) {
+    let mut restore = Restore {
+        active: ACTIVE.with(|a| a.borrow_mut().take()),
+        observed: false,
+    };
+
+    if let Some(a) = restore.active.as_mut() {
+        let next = a.ids.len();
+        let id = *a.ids.entry(a.path.clone()).or_insert(next);
+
+        let name = bhv.name();
+        let info = NodeInfo {
+            id: NodeId(id),
+            path: &a.path,
+            kind: bhv.kind(),
+            name: &name,
+        };
+
+        f(&mut *a.observer.borrow_mut(), &info);
+    }
+}
+
+/// Run `f` without reporting to the active observer, such as when running nodes on other threads.
+#[cfg(feature = "threads")]
+pub(crate) fn detached<R>(f: impl FnOnce() -> R) -> R {
+    if !maybe_observed() {
+        return f();
+    }
+
+    let _restore = Restore {
+        active: ACTIVE.with(|a| a.borrow_mut().take()),
+        observed: false,
+    };
+
+    f()
+}
+
+/// Report that `bhv`, the child at `index` of the node being run, is about to be updated.
+/// Used by nodes that run their children through [`detached`].
+#[cfg(feature = "threads")]
+pub(crate) fn enter<B: Bhv>(index: usize, bhv: &B) {
+    if maybe_observed() {
+        in_child(index, || report(bhv, |o, n| o.on_enter(n)));
+    }
+}
+
+/// Report that `bhv`, the child at `index` of the node being run, returned `status`.
+/// Used by nodes that run their children through [`detached`].
+#[cfg(feature = "threads")]
+pub(crate) fn status<B: Bhv>(index: usize, bhv: &B, status: Status) {
+    if maybe_observed() {
+        in_child(index, || report(bhv, |o, n| o.on_status(n, status)));
+    }
+}
+
+/// Run `f` with the child at `index` appended to the current path, if a tree is being observed.
+/// Returns `None` if no tree is being observed.
+fn in_child<R>(index: usize, f: impl FnOnce() -> R) -> Option<R> {
+    let active = ACTIVE.with(|a| match a.borrow_mut().as_mut() {
+        Some(a) => {
+            a.path.push(index);
+            true
+        }
+        None => false,
+    });
+
+    if !active {
+        return None;
+    }
+
+    let r = f();
+
+    ACTIVE.with(|a| {
+        if let Some(a) = a.borrow_mut().as_mut() {
+            a.path.pop();
+        }
+    });
+
+    Some(r)
+}
+
+/// Update `bhv`, the child at `index` of the node being run, reporting it to the active observer.
+#[inline(always)]
+pub(crate) fn update<B: Bhv>(index: usize, bhv: &mut B, ctx: &mut B::Context) -> Status {
+    if maybe_observed() {
+        update_observed(index, bhv, ctx)
+    } else {
+        bhv.update(ctx)
+    }
+}
+
+/// Reset `bhv`, the child at `index` of the node being run, reporting it to the active observer.
+#[inline(always)]
+pub(crate) fn reset<B: Bhv>(index: usize, bhv: &mut B, status: Status) {
+    if maybe_observed() {
+        reset_observed(index, bhv, status)
+    } else {
+        bhv.reset(status)
+    }
+}
+
+/// Halt `bhv`, the child at `index` of the node being run, reporting it to the active observer.
+#[inline(always)]
+pub(crate) fn halt<B: Bhv>(index: usize, bhv: &mut B, ctx: &mut B::Context) {
+    if maybe_observed() {
+        halt_observed(index, bhv, ctx)
+    } else {
+        bhv.halt(ctx)
+    }
+}
+
+// The observed paths are kept out of line, so that only the check above is inlined into the
+// composites (most notably the statically dispatched ones of `tuple`).
+
+#[cold]
+#[inline(never)]
+fn update_observed<B: Bhv>(index: usize, bhv: &mut B, ctx: &mut B::Context) -> Status {
+    match in_child(index, || observe_update(bhv, ctx)) {
+        Some(s) => s,
+        None => bhv.update(ctx),
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn reset_observed<B: Bhv>(index: usize, bhv: &mut B, status: Status) {
+    if in_child(index, || observe_reset(bhv, status)).is_none() {
+        bhv.reset(status)
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn halt_observed<B: Bhv>(index: usize, bhv: &mut B, ctx: &mut B::Context) {
+    if in_child(index, || observe_halt(bhv, ctx)).is_none() {
+        bhv.halt(ctx)
+    }
+}
+
+fn observe_update<B: Bhv>(bhv: &mut B, ctx: &mut B::Context) -> Status {
+    report(bhv, |o, n| o.on_enter(n));
+    let s = bhv.update(ctx);
+    report(bhv, |o, n| o.on_status(n, s));
+    s
+}
+
+fn observe_reset<B: Bhv>(bhv: &mut B, status: Status) {
+    report(bhv, |o, n| o.on_reset(n, status));
+    bhv.reset(status)
+}
+
+fn observe_halt<B: Bhv>(bhv: &mut B, ctx: &mut B::Context) {
+    report(bhv, |o, n| o.on_halt(n));
+    bhv.halt(ctx)
+}
+
+impl<B: Bhv, O: TreeObserver + 'static> Observed<B, O> {
+    #[inline]
+    pub(crate) fn new(bhv: B, observer: O) -> Self {
+        let mut ids = HashMap::new();
+        number(NodeRef::new(&bhv), &mut Vec::new(), &mut ids);
+
+        Self {
+            bhv,
+            observer: Rc::new(RefCell::new(observer)),
+            ids,
+        }
+    }
+
+    /// A reference to the observer.
+    #[inline]
+    pub fn observer(&self) -> Ref<'_, O> {
+        self.observer.borrow()
+    }
+
+    /// A mutable reference to the observer.
+    #[inline]
+    pub fn observer_mut(&self) -> RefMut<'_, O> {
+        self.observer.borrow_mut()
+    }
+
+    /// Run `f` with this tree as the one being observed, restoring the previous one afterwards.
+    fn activate<R>(&mut self, f: impl FnOnce(&mut B) -> R) -> R {
+        let active = Active {
+            observer: self.observer.clone(),
+            ids: std::mem::take(&mut self.ids),
+            path: Vec::new(),
+        };
+
+        OBSERVED.fetch_add(1, Ordering::Relaxed);
+
+        let restore = Restore {
+            active: ACTIVE.with(|a| a.borrow_mut().replace(active)),
+            observed: true,
+        };
+
+        let r = f(&mut self.bhv);
+
+        if let Some(a) = ACTIVE.with(|a| a.borrow_mut().take()) {
+            self.ids = a.ids;
+        }
+
+        drop(restore);
+        r
+    }
+}
+
+impl<B: Bhv, O: TreeObserver + 'static> Bhv for Observed<B, O> {
+    type Context = B::Context;
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        self.activate(|bhv| observe_update(bhv, ctx))
+    }
+
+    #[inline]
+    fn reset(&mut self, status: Status) {
+        self.activate(|bhv| observe_reset(bhv, status))
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.activate(|bhv| observe_halt(bhv, ctx))
+    }
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        self.bhv.kind()
+    }
+
+    #[inline]
+    fn name(&self) -> std::borrow::Cow<'_, str> {
+        self.bhv.name()
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.bhv.visit_children(f)
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.bhv.visit_children_mut(f)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        self.bhv.save_state()
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        self.bhv.load_state(state)
+    }
+}
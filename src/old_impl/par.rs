@@ -0,0 +1,396 @@
+use std::{borrow::BorrowMut, fmt, ops::Deref, sync::Arc};
+
+use crate::old_impl::{
+    core::{Bhv, Status},
+    observe, snapshot,
+};
+use crate::introspect::{NodeMut, NodeRef};
+
+/// A child node of [`ParWhenAny`] or [`ParWhenAll`], which can be run on another thread.
+pub type ParBhv<P> = Box<dyn Bhv<Context=P> + Send>;
+
+/// A context that can be split into one piece per child of a node running its children on
+/// multiple threads, such as [`ParWhenAll`].
+///
+/// The crate provides two ways of splitting a context:
+/// - [`Vec<T>`] gives each child exclusive access to one of its elements, so the `n`-th child runs
+///   on the `n`-th element.
+/// - [`Shared<T>`] gives each child a handle to the same value, which is only accessed through
+///   shared references.
+pub trait Split {
+    /// The context of the children.
+    type Part;
+
+    /// A piece of the context, given to a single child.
+    type Piece<'a>: BorrowMut<Self::Part> + Send
+        where
+            Self: 'a;
+
+    /// Split the context into `n` pieces, one for each child.
+    ///
+    /// # Panics
+    ///
+    /// Implementations may panic if the context cannot be split into `n` pieces.
+    fn split(&mut self, n: usize) -> Vec<Self::Piece<'_>>;
+}
+
+/// A context that is shared between the children of a node running on multiple threads, such as
+/// [`ParWhenAll`]. Each child runs with its own handle to the same value.
+///
+/// Since the value can only be accessed through shared references, any changes to it have to go
+/// through types such as [`std::sync::Mutex`] or atomics.
+#[derive(Debug, Default)]
+pub struct Shared<T>(pub Arc<T>);
+
+/// A node that runs its children at the same time on separate threads, until one of them
+/// completes successfully. This node is the multi-threaded version of [`crate::WhenAny`].
+///
+/// The children that have not completed yet are run on every update, each one with its own piece
+/// of the context as created by [`Split`]. Once a child succeeds, this node succeeds. If all the
+/// children fail, this node fails. Otherwise, [`Status::Running`] is returned. Once this node
+/// completes, the children that are still running are halted and the others are reset.
+///
+/// The children run on the global thread pool of [`rayon_core`], whose threads are reused between
+/// updates. Updates, resets and halts of the children are reported to [`crate::TreeObserver`]s, but
+/// the nodes inside of the children are not.
+pub struct ParWhenAny<S: Split> {
+    pub(crate) nodes: Box<[ParBhv<S::Part>]>,
+    pub(crate) statuses: Box<[Option<Status>]>,
+}
+
+/// A node that runs its children at the same time on separate threads, until all of them complete.
+/// This node is the multi-threaded version of [`crate::WhenAll`].
+///
+/// The children that have not completed yet are run on every update, each one with its own piece
+/// of the context as created by [`Split`]. Once a child fails, this node fails. If all the
+/// children succeed, this node succeeds. Otherwise, [`Status::Running`] is returned. Once this
+/// node completes, the children that are still running are halted and the others are reset.
+///
+/// The children run on the global thread pool of [`rayon_core`], whose threads are reused between
+/// updates. Updates, resets and halts of the children are reported to [`crate::TreeObserver`]s, but
+/// the nodes inside of the children are not.
+pub struct ParWhenAll<S: Split> {
+    pub(crate) nodes: Box<[ParBhv<S::Part>]>,
+    pub(crate) statuses: Box<[Option<Status>]>,
+}
+
+impl<T: Send> Split for Vec<T> {
+    type Part = T;
+    type Piece<'a> = &'a mut T
+        where
+            T: 'a;
+
+    /// # Panics
+    ///
+    /// Panics if the vector does not have exactly `n` elements.
+    fn split(&mut self, n: usize) -> Vec<Self::Piece<'_>> {
+        assert_eq!(self.len(), n, "the context should have one element for each child");
+        self.iter_mut().collect()
+    }
+}
+
+impl<T: Send + Sync> Split for Shared<T> {
+    type Part = Shared<T>;
+    type Piece<'a> = Shared<T>
+        where
+            T: 'a;
+
+    fn split(&mut self, n: usize) -> Vec<Self::Piece<'_>> {
+        vec![self.clone(); n]
+    }
+}
+
+impl<T> Shared<T> {
+    /// Create a shared context holding `value`.
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Update the nodes that have not completed yet, each on its own piece of `ctx`, on the thread pool.
+fn update_pending<S: Split>(nodes: &mut [ParBhv<S::Part>], statuses: &mut [Option<Status>], ctx: &mut S) {
+    let pending: Vec<_> = statuses.iter().map(|s| !is_done(*s)).collect();
+
+    for (i, node) in nodes.iter().enumerate().filter(|(i, _)| pending[*i]) {
+        observe::enter(i, node);
+    }
+
+    let mut pieces = ctx.split(nodes.len());
+
+    observe::detached(|| {
+        rayon_core::in_place_scope(|scope| {
+            let children = nodes.iter_mut().zip(pieces.iter_mut()).zip(statuses.iter_mut());
+
+            for ((node, piece), status) in children.filter(|(_, s)| !is_done(**s)) {
+                scope.spawn(move |_| *status = Some(node.update(piece.borrow_mut())));
+            }
+        })
+    });
+
+    for (i, node) in nodes.iter().enumerate().filter(|(i, _)| pending[*i]) {
+        observe::status(i, node, statuses[i].unwrap_or(Status::Running));
+    }
+}
+
+/// Halt the nodes that are still running and reset the ones that completed, clearing their statuses.
+fn finish<S: Split>(nodes: &mut [ParBhv<S::Part>], statuses: &mut [Option<Status>], ctx: &mut S) {
+    let mut pieces = ctx.split(nodes.len());
+    let children = nodes.iter_mut().zip(pieces.iter_mut()).zip(statuses.iter_mut());
+
+    for (i, ((node, piece), status)) in children.enumerate() {
+        match status.take() {
+            Some(Status::Running) => observe::halt(i, node, piece.borrow_mut()),
+            Some(s) => observe::reset(i, node, s),
+            None => {}
+        }
+    }
+}
+
+#[inline]
+fn is_done(status: Option<Status>) -> bool {
+    matches!(status, Some(Status::Success | Status::Failure))
+}
+
+impl<S: Split> ParWhenAny<S> {
+    #[inline]
+    pub fn new(bhvs: Box<[ParBhv<S::Part>]>) -> Self {
+        Self {
+            statuses: vec![None; bhvs.len()].into_boxed_slice(),
+            nodes: bhvs,
+        }
+    }
+}
+
+impl<S: Split> ParWhenAll<S> {
+    #[inline]
+    pub fn new(bhvs: Box<[ParBhv<S::Part>]>) -> Self {
+        Self {
+            statuses: vec![None; bhvs.len()].into_boxed_slice(),
+            nodes: bhvs,
+        }
+    }
+}
+
+impl<S: Split> Bhv for ParWhenAny<S> {
+    type Context = S;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "ParWhenAny"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.nodes.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.nodes.iter_mut().for_each(|n| f(NodeMut::new(n)))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        update_pending(&mut self.nodes, &mut self.statuses, ctx);
+
+        let status = if self.statuses.contains(&Some(Status::Success)) {
+            Status::Success
+        } else if self.statuses.iter().all(|s| *s == Some(Status::Failure)) {
+            Status::Failure
+        } else {
+            return Status::Running;
+        };
+
+        finish(&mut self.nodes, &mut self.statuses, ctx);
+        status
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        finish(&mut self.nodes, &mut self.statuses, ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        save_statuses(&self.statuses)
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        load_statuses(&mut self.statuses, state)
+    }
+}
+
+impl<S: Split> Bhv for ParWhenAll<S> {
+    type Context = S;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "ParWhenAll"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.nodes.iter().for_each(|n| f(NodeRef::new(n)))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.nodes.iter_mut().for_each(|n| f(NodeMut::new(n)))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        update_pending(&mut self.nodes, &mut self.statuses, ctx);
+
+        let status = if self.statuses.contains(&Some(Status::Failure)) {
+            Status::Failure
+        } else if self.statuses.iter().all(|s| *s == Some(Status::Success)) {
+            Status::Success
+        } else {
+            return Status::Running;
+        };
+
+        finish(&mut self.nodes, &mut self.statuses, ctx);
+        status
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        finish(&mut self.nodes, &mut self.statuses, ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        save_statuses(&self.statuses)
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        load_statuses(&mut self.statuses, state)
+    }
+}
+
+fn save_statuses(statuses: &[Option<Status>]) -> Vec<u64> {
+    statuses.iter().map(|s| snapshot::encode_status(*s)).collect()
+}
+
+/// Restore the statuses of the children from the state of a snapshot.
+fn load_statuses(statuses: &mut [Option<Status>], state: &[u64]) -> bool {
+    if state.len() != statuses.len() {
+        return false;
+    }
+
+    match state.iter().map(|v| snapshot::decode_status(*v)).collect::<Option<Vec<_>>>() {
+        Some(s) => {
+            statuses.copy_from_slice(&s);
+            true
+        }
+        None => false,
+    }
+}
+
+impl<S: Split> fmt::Debug for ParWhenAny<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<S: Split> fmt::Debug for ParWhenAll<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+/// A macro used to create a [`ParWhenAny`] from a list of behaviors, which are run on separate
+/// threads. Requires the `threads` feature.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicU32, Ordering};
+///
+/// use bhv::*;
+///
+/// #[derive(Default)]
+/// struct World {
+///     sightings: AtomicU32,
+/// }
+///
+/// // every scout looks around on its own thread, until one of them spots the target
+/// let scout = |spotted: bool| {
+///     async_action(move |w: &mut Shared<World>| {
+///         w.sightings.fetch_add(1, Ordering::Relaxed);
+///         if spotted { Status::Success } else { Status::Running }
+///     })
+/// };
+///
+/// let tree = par_when_any! { scout(false), scout(true), scout(false) };
+///
+/// let mut world = Shared::new(World::default());
+/// assert!(tree.execute(&mut world));
+///
+/// assert_eq!(world.sightings.load(Ordering::Relaxed), 3);
+/// ```
+#[macro_export]
+macro_rules! par_when_any {
+    () => {
+        compile_error!("`par_when_any` should have at least one argument!")
+    };
+    ($($x:expr),+$(,)?) => {
+        $crate::ParWhenAny::new(
+            Box::new([$(Box::new($x) as $crate::ParBhv<_>),+]),
+        )
+    };
+}
+
+/// A macro used to create a [`ParWhenAll`] from a list of behaviors, which are run on separate
+/// threads. Requires the `threads` feature.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// // each planner works on its own part of the context
+/// let plan = |steps: u32| {
+///     async_action(move |done: &mut u32| {
+///         *done += 1;
+///         if *done < steps { Status::Running } else { Status::Success }
+///     })
+/// };
+///
+/// let tree = par_when_all! { plan(1), plan(3), plan(2) };
+///
+/// let mut progress: Vec<u32> = vec![0, 0, 0];
+/// assert!(tree.execute(&mut progress));
+///
+/// // planners that are done are not run again
+/// assert_eq!(progress, [1, 3, 2]);
+/// ```
+#[macro_export]
+macro_rules! par_when_all {
+    () => {
+        compile_error!("`par_when_all` should have at least one argument!")
+    };
+    ($($x:expr),+$(,)?) => {
+        $crate::ParWhenAll::new(
+            Box::new([$(Box::new($x) as $crate::ParBhv<_>),+]),
+        )
+    };
+}
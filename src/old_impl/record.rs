@@ -0,0 +1,352 @@
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Bhv, BhvExt, NodeId, NodeInfo, Runner, Status, TreeObserver};
+
+/// A node that returned a status during a recorded tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Visit {
+    /// The identifier of the node.
+    pub id: NodeId,
+    /// The status returned by the node.
+    pub status: Status,
+}
+
+/// The nodes visited during a single update of the root of a tree, in the order they returned.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TickTrace {
+    pub visits: Vec<Visit>,
+}
+
+/// A recording of the execution of a tree, as created by a [`Recorder`].
+///
+/// Traces can be written to and read from files in a simple line-based text format. Under the
+/// `serde` feature, traces can also be (de)serialized with any `serde` format.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Trace {
+    pub ticks: Vec<TickTrace>,
+}
+
+/// A [`TreeObserver`] that records the status returned by each node of a tree, on every update
+/// of the root.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let tree = seq! {
+///     action(|v| *v += 1),
+///     cond(|v| *v > 1),
+/// };
+///
+/// let mut runner = Runner::new(tree.observed(Recorder::new()));
+///
+/// let mut ctx = 0;
+/// runner.tick(&mut ctx);
+/// runner.tick(&mut ctx);
+///
+/// let trace = runner.bhv().observer().trace().clone();
+///
+/// // the first time the condition fails, the second time it succeeds
+/// assert_eq!(trace.ticks.len(), 2);
+/// assert_eq!(trace.ticks[0].visits.last(), Some(&Visit { id: NodeId(0), status: Status::Failure }));
+/// assert_eq!(trace.ticks[1].visits.last(), Some(&Visit { id: NodeId(0), status: Status::Success }));
+///
+/// // the same tree behaves the same way given the same context
+/// let tree = seq! {
+///     action(|v| *v += 1),
+///     cond(|v| *v > 1),
+/// };
+///
+/// assert_eq!(replay(tree, &mut 0, &trace), Ok(()));
+/// ```
+#[derive(Default)]
+pub struct Recorder {
+    trace: Trace,
+}
+
+/// A [`TreeObserver`] that checks the status returned by each node of a tree against a [`Trace`],
+/// keeping the first difference found.
+///
+/// See [`replay`] for a simpler way to replay a trace.
+pub struct Replayer {
+    trace: Trace,
+    tick: usize,
+    visit: usize,
+    started: bool,
+    divergence: Option<Divergence>,
+}
+
+/// The first difference between a [`Trace`] and the replayed execution of a tree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Divergence {
+    /// The index of the tick where the difference was found.
+    pub tick: usize,
+    /// The index of the visit inside of the tick where the difference was found.
+    pub visit: usize,
+    /// The visit in the trace, if any.
+    pub expected: Option<Visit>,
+    /// The visit during the replay, if any.
+    pub actual: Option<Visit>,
+}
+
+impl Trace {
+    /// Write the trace in a line-based text format.
+    pub fn write_to(&self, mut w: impl Write) -> io::Result<()> {
+        writeln!(w, "bhv-trace 1")?;
+
+        for tick in &self.ticks {
+            writeln!(w, "tick")?;
+
+            for v in &tick.visits {
+                writeln!(w, "visit {} {:?}", v.id.0, v.status)?;
+            }
+        }
+
+        w.flush()
+    }
+
+    /// Read a trace written by [`Trace::write_to`].
+    pub fn read_from(r: impl BufRead) -> io::Result<Self> {
+        let mut lines = r.lines();
+
+        match lines.next().transpose()? {
+            Some(header) if header.trim() == "bhv-trace 1" => {}
+            _ => return Err(invalid_data("missing `bhv-trace 1` header")),
+        }
+
+        let mut trace = Trace::default();
+
+        for line in lines {
+            let line = line?;
+            let mut words = line.split_whitespace();
+
+            match words.next() {
+                None => continue,
+                Some("tick") => trace.ticks.push(TickTrace::default()),
+                Some("visit") => {
+                    let id = words
+                        .next()
+                        .and_then(|w| w.parse().ok())
+                        .ok_or_else(|| invalid_data(&format!("bad node id in `{}`", line)))?;
+
+                    let status = match words.next() {
+                        Some("Running") => Status::Running,
+                        Some("Success") => Status::Success,
+                        Some("Failure") => Status::Failure,
+                        _ => return Err(invalid_data(&format!("bad status in `{}`", line))),
+                    };
+
+                    trace
+                        .ticks
+                        .last_mut()
+                        .ok_or_else(|| invalid_data("visit before the first tick"))?
+                        .visits
+                        .push(Visit {
+                            id: NodeId(id),
+                            status,
+                        });
+                }
+                Some(_) => return Err(invalid_data(&format!("unknown line `{}`", line))),
+            }
+        }
+
+        Ok(trace)
+    }
+
+    /// Write the trace to the file at `path`, replacing it if it exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// let trace = Trace {
+    ///     ticks: vec![TickTrace {
+    ///         visits: vec![Visit { id: NodeId(0), status: Status::Success }],
+    ///     }],
+    /// };
+    ///
+    /// let path = std::env::temp_dir().join("bhv_trace_save_example.txt");
+    /// trace.save(&path).unwrap();
+    ///
+    /// assert_eq!(Trace::load(&path).unwrap(), trace);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Read the trace in the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+impl Recorder {
+    /// Create a recorder with an empty trace.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The trace recorded so far.
+    #[inline]
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    /// Consume the recorder and return the recorded trace.
+    #[inline]
+    pub fn into_trace(self) -> Trace {
+        self.trace
+    }
+}
+
+impl TreeObserver for Recorder {
+    fn on_enter(&mut self, node: &NodeInfo<'_>) {
+        if node.path.is_empty() {
+            self.trace.ticks.push(TickTrace::default());
+        }
+    }
+
+    fn on_status(&mut self, node: &NodeInfo<'_>, status: Status) {
+        if let Some(tick) = self.trace.ticks.last_mut() {
+            tick.visits.push(Visit {
+                id: node.id,
+                status,
+            });
+        }
+    }
+}
+
+impl Replayer {
+    /// Create a replayer that checks against `trace`.
+    #[inline]
+    pub fn new(trace: Trace) -> Self {
+        Self {
+            trace,
+            tick: 0,
+            visit: 0,
+            started: false,
+            divergence: None,
+        }
+    }
+
+    /// The first difference found so far, if any.
+    #[inline]
+    pub fn divergence(&self) -> Option<Divergence> {
+        self.divergence
+    }
+
+    /// The number of ticks replayed so far.
+    #[inline]
+    pub fn ticks(&self) -> usize {
+        self.tick + self.started as usize
+    }
+
+    fn expected(&self) -> Option<Visit> {
+        self.trace
+            .ticks
+            .get(self.tick)
+            .and_then(|t| t.visits.get(self.visit))
+            .copied()
+    }
+
+    fn diverge(&mut self, expected: Option<Visit>, actual: Option<Visit>) {
+        if self.divergence.is_none() {
+            self.divergence = Some(Divergence {
+                tick: self.tick,
+                visit: self.visit,
+                expected,
+                actual,
+            });
+        }
+    }
+}
+
+impl TreeObserver for Replayer {
+    fn on_enter(&mut self, node: &NodeInfo<'_>) {
+        if node.path.is_empty() {
+            if self.started {
+                self.tick += 1;
+            }
+
+            self.started = true;
+            self.visit = 0;
+        }
+    }
+
+    fn on_status(&mut self, node: &NodeInfo<'_>, status: Status) {
+        let actual = Visit {
+            id: node.id,
+            status,
+        };
+        let expected = self.expected();
+
+        if expected != Some(actual) {
+            self.diverge(expected, Some(actual));
+        }
+
+        self.visit += 1;
+
+        // the tick is over, but the trace has more visits
+        if node.path.is_empty() {
+            if let Some(expected) = self.expected() {
+                self.diverge(Some(expected), None);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tick {}, visit {}: expected ", self.tick, self.visit)?;
+
+        match self.expected {
+            Some(v) => write!(f, "node {} to return {:?}", v.id.0, v.status)?,
+            None => write!(f, "no more visits")?,
+        }
+
+        match self.actual {
+            Some(v) => write!(f, ", but node {} returned {:?}", v.id.0, v.status),
+            None => write!(f, ", but the tick ended"),
+        }
+    }
+}
+
+impl Error for Divergence {}
+
+/// Run `bhv` once for every tick of `trace`, checking that its nodes return the same statuses as
+/// in the trace. Returns the first difference found, if any.
+///
+/// The tree is updated through a [`Runner`], so it is reset every time it completes.
+pub fn replay<B: Bhv>(bhv: B, ctx: &mut B::Context, trace: &Trace) -> Result<(), Divergence> {
+    let mut runner = Runner::new(bhv.observed(Replayer::new(trace.clone())));
+
+    for _ in 0..trace.ticks.len() {
+        runner.tick(ctx);
+
+        if let Some(d) = runner.bhv().observer().divergence() {
+            return Err(d);
+        }
+    }
+
+    Ok(())
+}
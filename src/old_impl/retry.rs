@@ -0,0 +1,382 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{old_impl::{observe, time}, Bhv, Clock, NodeMut, NodeRef, Status, SystemClock};
+
+/// A counter of the failed attempts of a retrying node, shared between clones.
+///
+/// A clone of the counter can be moved into other nodes (such as a [`crate::cond`]) to inspect
+/// how many times the retrying node failed so far. The counter goes back to zero when the
+/// retrying node is reset. Clones of the retrying node itself get a counter of their own.
+#[derive(Clone, Debug, Default)]
+pub struct Attempts(Arc<AtomicU32>);
+
+/// The time to wait between two attempts of a retrying node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Wait {
+    /// Wait for the given number of updates.
+    Ticks(u32),
+    /// Wait for the given duration, as measured by a [`Clock`].
+    Time(Duration),
+}
+
+/// How the time to wait between attempts of [`RetryWithBackoff`] grows with each failed attempt.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backoff {
+    /// Wait the same amount of time after every attempt.
+    Fixed(Wait),
+    /// Wait `n` times the given amount of time after the `n`-th attempt.
+    Linear(Wait),
+    /// Wait `2^(n - 1)` times the given amount of time after the `n`-th attempt.
+    Exponential(Wait),
+}
+
+/// A decorator that runs the given node again when it fails, up to a given number of attempts.
+///
+/// It returns [`Status::Running`] while the node is being retried and [`Status::Failure`] once
+/// the node fails for the last time. Otherwise, the status of the node is returned.
+///
+/// Cloning the node gives the clone its own counter of failed attempts, starting from zero.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let mut scout = async_action(|_: &mut ()| Status::Failure).retry(3);
+/// let mut other = scout.clone();
+///
+/// scout.update(&mut ());
+/// scout.update(&mut ());
+///
+/// assert_eq!(scout.attempts().get(), 2);
+/// assert_eq!(other.attempts().get(), 0);
+///
+/// // the clone still gets all of its attempts
+/// assert_eq!(other.update(&mut ()), Status::Running);
+/// assert_eq!(other.attempts().get(), 1);
+/// ```
+pub struct Retry<B: Bhv> {
+    pub(crate) bhv: B,
+    pub(crate) max: u32,
+    pub(crate) attempts: Attempts,
+}
+
+/// A decorator that runs the given node again when it fails, up to a given number of attempts,
+/// waiting between the attempts according to a [`Backoff`].
+///
+/// It returns [`Status::Running`] while the node is being retried or waited for and
+/// [`Status::Failure`] once the node fails for the last time. Otherwise, the status of the node is
+/// returned.
+///
+/// Cloning the node gives the clone its own counter of failed attempts, starting from zero, and
+/// the clone does not wait for the next attempt.
+pub struct RetryWithBackoff<B: Bhv, K: Clock = SystemClock> {
+    pub(crate) bhv: B,
+    pub(crate) max: u32,
+    pub(crate) backoff: Backoff,
+    pub(crate) clock: K,
+    pub(crate) attempts: Attempts,
+    pub(crate) waiting: Option<Waiting>,
+}
+
+/// The state of a [`RetryWithBackoff`] that is waiting for the next attempt.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Waiting {
+    Ticks(u32),
+    Until(Duration),
+}
+
+impl Attempts {
+    /// The number of failed attempts so far.
+    #[inline]
+    pub fn get(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Count a failed attempt and return the new number of failed attempts.
+    #[inline]
+    pub(crate) fn increment(&self) -> u32 {
+        self.0.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    #[inline]
+    pub(crate) fn clear(&self) {
+        self.set(0)
+    }
+
+    #[inline]
+    pub(crate) fn set(&self, attempts: u32) {
+        self.0.store(attempts, Ordering::Relaxed)
+    }
+}
+
+impl Wait {
+    /// Multiply the time to wait by `n`, saturating on overflow.
+    fn times(self, n: u32) -> Self {
+        match self {
+            Self::Ticks(t) => Self::Ticks(t.saturating_mul(n)),
+            Self::Time(d) => Self::Time(d.checked_mul(n).unwrap_or(Duration::MAX)),
+        }
+    }
+}
+
+impl Backoff {
+    /// The time to wait after the `attempt`-th failed attempt, counting from 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bhv::*;
+    ///
+    /// let backoff = Backoff::Exponential(Wait::Ticks(2));
+    ///
+    /// assert_eq!(backoff.wait(1), Wait::Ticks(2));
+    /// assert_eq!(backoff.wait(2), Wait::Ticks(4));
+    /// assert_eq!(backoff.wait(3), Wait::Ticks(8));
+    /// ```
+    pub fn wait(&self, attempt: u32) -> Wait {
+        match *self {
+            Self::Fixed(w) => w,
+            Self::Linear(w) => w.times(attempt),
+            Self::Exponential(w) => w.times(
+                1u32.checked_shl(attempt.saturating_sub(1))
+                    .unwrap_or(u32::MAX),
+            ),
+        }
+    }
+}
+
+impl<B: Bhv> Retry<B> {
+    /// A counter of the failed attempts of this node.
+    #[inline]
+    pub fn attempts(&self) -> Attempts {
+        self.attempts.clone()
+    }
+}
+
+impl<B: Bhv, K: Clock> RetryWithBackoff<B, K> {
+    /// A counter of the failed attempts of this node.
+    #[inline]
+    pub fn attempts(&self) -> Attempts {
+        self.attempts.clone()
+    }
+
+    /// Use `clock` to measure time instead of the current clock.
+    #[inline]
+    pub fn with_clock<K2: Clock>(self, clock: K2) -> RetryWithBackoff<B, K2> {
+        RetryWithBackoff {
+            bhv: self.bhv,
+            max: self.max,
+            backoff: self.backoff,
+            clock,
+            attempts: self.attempts,
+            waiting: None,
+        }
+    }
+}
+
+impl<B: Bhv + Clone> Clone for Retry<B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            bhv: self.bhv.clone(),
+            max: self.max,
+            attempts: Attempts::default(),
+        }
+    }
+}
+
+impl<B: Bhv + Clone, K: Clock + Clone> Clone for RetryWithBackoff<B, K> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            bhv: self.bhv.clone(),
+            max: self.max,
+            backoff: self.backoff,
+            clock: self.clock.clone(),
+            attempts: Attempts::default(),
+            waiting: None,
+        }
+    }
+}
+
+impl<B: Bhv> Bhv for Retry<B> {
+    type Context = B::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Retry"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Retry({})", self.max))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        match observe::update(0, &mut self.bhv, ctx) {
+            Status::Failure => {
+                if self.attempts.increment() >= self.max {
+                    Status::Failure
+                } else {
+                    observe::reset(0, &mut self.bhv, Status::Failure);
+                    Status::Running
+                }
+            }
+            s => s,
+        }
+    }
+
+    fn reset(&mut self, _status: Status) {
+        observe::reset(0, &mut self.bhv, _status);
+        self.attempts.clear();
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.bhv, ctx);
+        self.attempts.clear();
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.attempts.get() as u64]
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match *state {
+            [attempts] if attempts <= self.max as u64 => {
+                self.attempts.set(attempts as u32);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<B: Bhv, K: Clock> Bhv for RetryWithBackoff<B, K> {
+    type Context = B::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "RetryWithBackoff"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("RetryWithBackoff({}, {:?})", self.max, self.backoff))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        match &mut self.waiting {
+            Some(Waiting::Ticks(n)) if *n > 0 => {
+                *n -= 1;
+                return Status::Running;
+            }
+            Some(Waiting::Until(t)) if self.clock.now() < *t => return Status::Running,
+            _ => self.waiting = None,
+        }
+
+        match observe::update(0, &mut self.bhv, ctx) {
+            Status::Failure => {
+                let n = self.attempts.increment();
+
+                if n >= self.max {
+                    Status::Failure
+                } else {
+                    observe::reset(0, &mut self.bhv, Status::Failure);
+                    self.waiting = Some(match self.backoff.wait(n) {
+                        Wait::Ticks(t) => Waiting::Ticks(t),
+                        Wait::Time(d) => Waiting::Until(self.clock.now().saturating_add(d)),
+                    });
+
+                    Status::Running
+                }
+            }
+            s => s,
+        }
+    }
+
+    fn reset(&mut self, _status: Status) {
+        observe::reset(0, &mut self.bhv, _status);
+        self.attempts.clear();
+        self.waiting = None;
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        // while waiting, the node is not running
+        if self.waiting.take().is_none() {
+            observe::halt(0, &mut self.bhv, ctx);
+        }
+
+        self.attempts.clear();
+    }
+
+    /// The state is the number of attempts, followed by the kind and amount of waiting left if the
+    /// node is waiting.
+    fn save_state(&self) -> Vec<u64> {
+        let attempts = self.attempts.get() as u64;
+
+        match self.waiting {
+            None => vec![attempts],
+            Some(Waiting::Ticks(n)) => vec![attempts, 0, n as u64],
+            Some(Waiting::Until(t)) => vec![attempts, 1, time::time_left(&self.clock, t)],
+        }
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        let (attempts, waiting) = match *state {
+            [a] => (a, None),
+            [a, 0, n] if n <= u32::MAX as u64 => (a, Some(Waiting::Ticks(n as u32))),
+            [a, 1, left] => (a, Some(Waiting::Until(time::time_after(&self.clock, left)))),
+            _ => return false,
+        };
+
+        if attempts > self.max as u64 {
+            return false;
+        }
+
+        self.attempts.set(attempts as u32);
+        self.waiting = waiting;
+        true
+    }
+}
+
+impl<B: Bhv> fmt::Debug for Retry<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv, K: Clock> fmt::Debug for RetryWithBackoff<B, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{Bhv, Status};
+
+/// A wrapper that owns a behavior tree and runs it one step at a time, without consuming it.
+///
+/// Unlike [`Bhv::execute`], which blocks until the tree is done, [`Runner::tick`] updates the tree
+/// only once, which makes it possible to store the tree inside of some other object (such as a
+/// game entity) and run it once per frame. After the tree returns a status other than
+/// [`Status::Running`], it is reset so that the next tick starts the tree over.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let mut runner = Runner::new(seq! {
+///     action(|v| *v += 1),
+///     cond(|v| *v % 3 == 0),
+/// });
+///
+/// let mut ctx = 0;
+///
+/// assert_eq!(runner.tick(&mut ctx), Status::Failure); // ctx == 1
+/// assert_eq!(runner.tick(&mut ctx), Status::Failure); // ctx == 2
+/// assert_eq!(runner.tick(&mut ctx), Status::Success); // ctx == 3
+///
+/// assert_eq!(runner.ticks(), 3);
+/// assert_eq!(runner.last_status(), Some(Status::Success));
+/// ```
+pub struct Runner<B: Bhv> {
+    bhv: B,
+    ticks: u64,
+    last_status: Option<Status>,
+}
+
+impl<B: Bhv> Runner<B> {
+    /// Create a runner that owns the given tree.
+    #[inline]
+    pub fn new(bhv: B) -> Self {
+        Self {
+            bhv,
+            ticks: 0,
+            last_status: None,
+        }
+    }
+
+    /// Update the tree once and return its status.
+    ///
+    /// If the tree completes, it is reset before this function returns.
+    pub fn tick(&mut self, ctx: &mut B::Context) -> Status {
+        let s = self.bhv.update(ctx);
+
+        if s != Status::Running {
+            self.bhv.reset(s);
+        }
+
+        self.ticks += 1;
+        self.last_status = Some(s);
+        s
+    }
+
+    /// Interrupt the tree if it is running, bringing it back to its initial state.
+    #[inline]
+    pub fn halt(&mut self, ctx: &mut B::Context) {
+        self.bhv.halt(ctx)
+    }
+
+    /// The number of times [`Runner::tick`] has been called.
+    #[inline]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// The status returned by the last call of [`Runner::tick`], if any.
+    #[inline]
+    pub fn last_status(&self) -> Option<Status> {
+        self.last_status
+    }
+
+    /// A reference to the tree owned by this runner.
+    #[inline]
+    pub fn bhv(&self) -> &B {
+        &self.bhv
+    }
+
+    /// A mutable reference to the tree owned by this runner.
+    #[inline]
+    pub fn bhv_mut(&mut self) -> &mut B {
+        &mut self.bhv
+    }
+
+    /// Consume the runner and return the tree it owns.
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.bhv
+    }
+}
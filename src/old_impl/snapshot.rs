@@ -0,0 +1,200 @@
+use std::{error::Error, fmt};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Bhv, NodeMut, NodeRef, Status};
+
+/// The runtime state of a tree, as saved by [`snapshot`].
+///
+/// Each node of the tree is saved with its kind, its own state (see [`Bhv::save_state`]) and the
+/// snapshots of its children. Under the `serde` feature, snapshots can be (de)serialized with any
+/// `serde` format.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot {
+    /// The kind of the node. See [`Bhv::kind`].
+    pub kind: String,
+    /// The state of the node, not including its children.
+    pub state: Vec<u64>,
+    /// The snapshots of the children of the node, in order.
+    pub children: Vec<Snapshot>,
+}
+
+/// The error returned by [`restore`] when a snapshot cannot be restored into a tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RestoreError {
+    /// The kind or number of children of a node of the tree does not match the snapshot.
+    ShapeMismatch {
+        /// The indices of the children to go through to reach the node that does not fit,
+        /// starting from the root.
+        path: Vec<usize>,
+        /// A description of the node in the tree.
+        expected: String,
+        /// A description of the node in the snapshot.
+        found: String,
+    },
+    /// The state saved for a node was rejected by [`Bhv::load_state`].
+    InvalidState {
+        /// The indices of the children to go through to reach the node, starting from the root.
+        path: Vec<usize>,
+        /// The kind of the node.
+        kind: String,
+        /// The rejected state.
+        state: Vec<u64>,
+    },
+}
+
+impl fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShapeMismatch { path, expected, found } => write!(
+                f,
+                "snapshot does not fit the tree at {:?}: expected {}, found {}",
+                path, expected, found,
+            ),
+            Self::InvalidState { path, kind, state } => write!(
+                f,
+                "invalid state {:?} for {} at {:?}",
+                state, kind, path,
+            ),
+        }
+    }
+}
+
+impl Error for RestoreError {}
+
+/// Save the runtime state of `bhv` and all of its descendants.
+///
+/// Only the state of the nodes is saved, not the context or values captured by closures. Times
+/// measured by a [`crate::Clock`] are saved relative to the time of the snapshot, so that a tree
+/// can be restored with a clock that started at a different time.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// fn patrol() -> impl Bhv<Context = Vec<&'static str>> {
+///     seq! {
+///         action(|log: &mut Vec<_>| log.push("a")),
+///         wait_ticks(1),
+///         action(|log: &mut Vec<_>| log.push("b")),
+///     }
+/// }
+///
+/// let mut tree = patrol();
+/// let mut log = Vec::new();
+/// tree.update(&mut log);
+///
+/// // save in the middle of the sequence...
+/// let saved = snapshot(&tree);
+///
+/// // ...and continue from there in a new tree
+/// let mut tree = patrol();
+/// restore(&mut tree, &saved).unwrap();
+///
+/// assert_eq!(tree.update(&mut log), Status::Success);
+/// assert_eq!(log, ["a", "b"]);
+///
+/// // a tree of a different shape is rejected
+/// let mut other = sel! { action(|_: &mut Vec<&str>| {}) };
+/// assert!(matches!(restore(&mut other, &saved), Err(RestoreError::ShapeMismatch { .. })));
+///
+/// // and so is a state that a node cannot load
+/// let mut broken = saved.clone();
+/// broken.state = vec![7];
+/// assert!(matches!(restore(&mut tree, &broken), Err(RestoreError::InvalidState { .. })));
+/// ```
+pub fn snapshot<B: Bhv>(bhv: &B) -> Snapshot {
+    save(NodeRef::new(bhv))
+}
+
+/// Restore the runtime state saved by [`snapshot`] into `bhv`, which should be built the same way
+/// as the tree that was saved.
+///
+/// The tree is left untouched if the snapshot cannot be restored: the shape of the tree is checked
+/// before anything is loaded, and if the state of a node is rejected by [`Bhv::load_state`], the
+/// nodes loaded before it are put back into the state they were in.
+pub fn restore<B: Bhv>(bhv: &mut B, snapshot: &Snapshot) -> Result<(), RestoreError> {
+    check(NodeRef::new(bhv), snapshot, &mut Vec::new())?;
+
+    let previous = save(NodeRef::new(bhv));
+
+    load(NodeMut::new(bhv), snapshot, &mut Vec::new()).inspect_err(|_| {
+        // the previous state was saved by the nodes themselves, so loading it back cannot fail
+        let _ = load(NodeMut::new(bhv), &previous, &mut Vec::new());
+    })
+}
+
+fn save(node: NodeRef<'_>) -> Snapshot {
+    Snapshot {
+        kind: node.kind().to_string(),
+        state: node.save_state(),
+        children: node.children().into_iter().map(save).collect(),
+    }
+}
+
+fn check(node: NodeRef<'_>, snapshot: &Snapshot, path: &mut Vec<usize>) -> Result<(), RestoreError> {
+    let children = node.children();
+
+    if node.kind() != snapshot.kind || children.len() != snapshot.children.len() {
+        return Err(RestoreError::ShapeMismatch {
+            path: path.clone(),
+            expected: format!("{} with {} children", node.kind(), children.len()),
+            found: format!("{} with {} children", snapshot.kind, snapshot.children.len()),
+        });
+    }
+
+    for (i, (c, s)) in children.into_iter().zip(&snapshot.children).enumerate() {
+        path.push(i);
+        check(c, s, path)?;
+        path.pop();
+    }
+
+    Ok(())
+}
+
+fn load(mut node: NodeMut<'_>, snapshot: &Snapshot, path: &mut Vec<usize>) -> Result<(), RestoreError> {
+    if !node.load_state(&snapshot.state) {
+        return Err(RestoreError::InvalidState {
+            path: path.clone(),
+            kind: snapshot.kind.clone(),
+            state: snapshot.state.clone(),
+        });
+    }
+
+    let mut children = snapshot.children.iter().enumerate();
+    let mut result = Ok(());
+
+    node.visit_children_mut(&mut |c| {
+        if let (Ok(()), Some((i, s))) = (&result, children.next()) {
+            path.push(i);
+            result = load(c, s, path);
+            path.pop();
+        }
+    });
+
+    result
+}
+
+/// Encode an optional status as a single value of a snapshot.
+pub(crate) fn encode_status(status: Option<Status>) -> u64 {
+    match status {
+        None => 0,
+        Some(Status::Running) => 1,
+        Some(Status::Success) => 2,
+        Some(Status::Failure) => 3,
+    }
+}
+
+/// Decode a value created by [`encode_status`].
+pub(crate) fn decode_status(value: u64) -> Option<Option<Status>> {
+    match value {
+        0 => Some(None),
+        1 => Some(Some(Status::Running)),
+        2 => Some(Some(Status::Success)),
+        3 => Some(Some(Status::Failure)),
+        _ => None,
+    }
+}
@@ -0,0 +1,503 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{old_impl::observe, Bhv, NodeMut, NodeRef, Status};
+
+/// A source of time for nodes that depend on it.
+///
+/// The time is measured as the duration elapsed since some fixed point, which is up to the clock.
+pub trait Clock {
+    /// The time elapsed since the starting point of the clock.
+    fn now(&self) -> Duration;
+}
+
+/// A [`Clock`] that measures real time, starting from the moment it was created.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock(Instant);
+
+/// A [`Clock`] that only moves forward when told so, useful for deterministic testing.
+///
+/// Clones of a mock clock share the same time, so a clone can be given to the nodes of a tree
+/// and another one can be used to advance the time.
+#[derive(Clone, Debug, Default)]
+pub struct MockClock(Arc<AtomicU64>);
+
+/// A decorator that fails if the given node runs for longer than a time limit, halting the node.
+#[derive(Clone)]
+pub struct Timeout<B: Bhv, K: Clock = SystemClock> {
+    pub(crate) bhv: B,
+    pub(crate) limit: Duration,
+    pub(crate) clock: K,
+    pub(crate) deadline: Option<Duration>,
+}
+
+/// A decorator that waits for some time before running the given node,
+/// returning [`Status::Running`] while waiting.
+#[derive(Clone)]
+pub struct Delay<B: Bhv, K: Clock = SystemClock> {
+    pub(crate) bhv: B,
+    pub(crate) delay: Duration,
+    pub(crate) clock: K,
+    pub(crate) ready_at: Option<Duration>,
+}
+
+/// A decorator that prevents the given node from running again for some time after it completes,
+/// returning [`Status::Failure`] in the meantime.
+#[derive(Clone)]
+pub struct Cooldown<B: Bhv, K: Clock = SystemClock> {
+    pub(crate) bhv: B,
+    pub(crate) cooldown: Duration,
+    pub(crate) clock: K,
+    pub(crate) ready_at: Option<Duration>,
+    pub(crate) running: bool,
+}
+
+/// A decorator that runs the given node at most a given number of times per second,
+/// returning [`Status::Running`] when the node is skipped.
+#[derive(Clone)]
+pub struct Throttle<B: Bhv, K: Clock = SystemClock> {
+    pub(crate) bhv: B,
+    pub(crate) interval: Duration,
+    pub(crate) clock: K,
+    pub(crate) next: Option<Duration>,
+}
+
+impl SystemClock {
+    /// Create a clock that starts counting from now.
+    #[inline]
+    pub fn new() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Default for SystemClock {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+impl MockClock {
+    /// Create a clock that starts at zero.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the time of the clock forward by `d`, stopping at the latest time the clock can hold
+    /// (about 584 years).
+    #[inline]
+    pub fn advance(&self, d: Duration) {
+        let d = nanos(d);
+        let _ = self.0.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |t| Some(t.saturating_add(d)));
+    }
+
+    /// Set the time of the clock to `t`, or to the latest time the clock can hold if `t` is later.
+    #[inline]
+    pub fn set(&self, t: Duration) {
+        self.0.store(nanos(t), Ordering::Relaxed);
+    }
+}
+
+impl Clock for MockClock {
+    #[inline]
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// The nanoseconds in `d`, saturating on overflow.
+#[inline]
+fn nanos(d: Duration) -> u64 {
+    u64::try_from(d.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// The time left from now until `t` in nanoseconds, saturating on overflow.
+///
+/// Times are saved in snapshots relative to the current time, since clocks are free to choose
+/// their starting point.
+pub(crate) fn time_left<K: Clock>(clock: &K, t: Duration) -> u64 {
+    nanos(t.saturating_sub(clock.now()))
+}
+
+/// The time after `left` nanoseconds from now, the opposite of [`time_left`].
+pub(crate) fn time_after<K: Clock>(clock: &K, left: u64) -> Duration {
+    clock.now().saturating_add(Duration::from_nanos(left))
+}
+
+/// Save an optional time yet to come as the state of a snapshot.
+fn save_deadline<K: Clock>(clock: &K, deadline: Option<Duration>) -> Vec<u64> {
+    deadline.map(|t| time_left(clock, t)).into_iter().collect()
+}
+
+/// Restore an optional time saved by [`save_deadline`], or `None` if the state is not valid.
+fn load_deadline<K: Clock>(clock: &K, state: &[u64]) -> Option<Option<Duration>> {
+    match *state {
+        [] => Some(None),
+        [left] => Some(Some(time_after(clock, left))),
+        _ => None,
+    }
+}
+
+impl<B: Bhv, K: Clock> Timeout<B, K> {
+    /// Use `clock` to measure time instead of the current clock.
+    #[inline]
+    pub fn with_clock<K2: Clock>(self, clock: K2) -> Timeout<B, K2> {
+        Timeout {
+            bhv: self.bhv,
+            limit: self.limit,
+            clock,
+            deadline: None,
+        }
+    }
+}
+
+impl<B: Bhv, K: Clock> Delay<B, K> {
+    /// Use `clock` to measure time instead of the current clock.
+    #[inline]
+    pub fn with_clock<K2: Clock>(self, clock: K2) -> Delay<B, K2> {
+        Delay {
+            bhv: self.bhv,
+            delay: self.delay,
+            clock,
+            ready_at: None,
+        }
+    }
+}
+
+impl<B: Bhv, K: Clock> Cooldown<B, K> {
+    /// Use `clock` to measure time instead of the current clock.
+    #[inline]
+    pub fn with_clock<K2: Clock>(self, clock: K2) -> Cooldown<B, K2> {
+        Cooldown {
+            bhv: self.bhv,
+            cooldown: self.cooldown,
+            clock,
+            ready_at: None,
+            running: false,
+        }
+    }
+}
+
+impl<B: Bhv, K: Clock> Throttle<B, K> {
+    /// Use `clock` to measure time instead of the current clock.
+    #[inline]
+    pub fn with_clock<K2: Clock>(self, clock: K2) -> Throttle<B, K2> {
+        Throttle {
+            bhv: self.bhv,
+            interval: self.interval,
+            clock,
+            next: None,
+        }
+    }
+}
+
+impl<B: Bhv, K: Clock> Bhv for Timeout<B, K> {
+    type Context = B::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Timeout"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Timeout({:?})", self.limit))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        let now = self.clock.now();
+
+        match self.deadline {
+            Some(deadline) if now >= deadline => {
+                self.halt(ctx);
+                return Status::Failure;
+            }
+            None => self.deadline = Some(now.saturating_add(self.limit)),
+            _ => {}
+        }
+
+        let s = observe::update(0, &mut self.bhv, ctx);
+        if s != Status::Running {
+            self.deadline = None;
+        }
+
+        s
+    }
+
+    fn reset(&mut self, _status: Status) {
+        observe::reset(0, &mut self.bhv, _status);
+        self.deadline = None;
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.bhv, ctx);
+        self.deadline = None;
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        save_deadline(&self.clock, self.deadline)
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match load_deadline(&self.clock, state) {
+            Some(deadline) => {
+                self.deadline = deadline;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<B: Bhv, K: Clock> Bhv for Delay<B, K> {
+    type Context = B::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Delay"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Delay({:?})", self.delay))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        let now = self.clock.now();
+        let ready_at = *self.ready_at.get_or_insert(now.saturating_add(self.delay));
+
+        if now < ready_at {
+            return Status::Running;
+        }
+
+        let s = observe::update(0, &mut self.bhv, ctx);
+        if s != Status::Running {
+            self.ready_at = None;
+        }
+
+        s
+    }
+
+    fn reset(&mut self, _status: Status) {
+        observe::reset(0, &mut self.bhv, _status);
+        self.ready_at = None;
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        if let Some(ready_at) = self.ready_at.take() {
+            // the node only runs once the delay is over
+            if self.clock.now() >= ready_at {
+                observe::halt(0, &mut self.bhv, ctx);
+            }
+        }
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        save_deadline(&self.clock, self.ready_at)
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match load_deadline(&self.clock, state) {
+            Some(ready_at) => {
+                self.ready_at = ready_at;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<B: Bhv, K: Clock> Bhv for Cooldown<B, K> {
+    type Context = B::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Cooldown"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Cooldown({:?})", self.cooldown))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        let now = self.clock.now();
+
+        if !self.running && self.ready_at.is_some_and(|t| now < t) {
+            return Status::Failure;
+        }
+
+        let s = observe::update(0, &mut self.bhv, ctx);
+        self.running = s == Status::Running;
+
+        if s != Status::Running {
+            self.ready_at = Some(now.saturating_add(self.cooldown));
+        }
+
+        s
+    }
+
+    #[inline]
+    fn reset(&mut self, _status: Status) {
+        // the cooldown outlives the completion of the node
+        observe::reset(0, &mut self.bhv, _status)
+    }
+
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.bhv, ctx);
+        self.running = false;
+    }
+
+    /// The state is whether the node is running, followed by the time left until the node is
+    /// ready, if any.
+    fn save_state(&self) -> Vec<u64> {
+        let mut state = vec![self.running as u64];
+        state.extend(save_deadline(&self.clock, self.ready_at));
+        state
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match state.split_first() {
+            Some((&running @ (0 | 1), rest)) => match load_deadline(&self.clock, rest) {
+                Some(ready_at) => {
+                    self.running = running == 1;
+                    self.ready_at = ready_at;
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl<B: Bhv, K: Clock> Bhv for Throttle<B, K> {
+    type Context = B::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Throttle"
+    }
+
+    #[inline]
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("Throttle({:?})", self.interval))
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        f(NodeRef::new(&self.bhv))
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        f(NodeMut::new(&mut self.bhv))
+    }
+
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        let now = self.clock.now();
+
+        if self.next.is_some_and(|t| now < t) {
+            return Status::Running;
+        }
+
+        self.next = Some(now.saturating_add(self.interval));
+        observe::update(0, &mut self.bhv, ctx)
+    }
+
+    #[inline]
+    fn reset(&mut self, _status: Status) {
+        observe::reset(0, &mut self.bhv, _status)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        observe::halt(0, &mut self.bhv, ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        save_deadline(&self.clock, self.next)
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match load_deadline(&self.clock, state) {
+            Some(next) => {
+                self.next = next;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<B: Bhv, K: Clock> fmt::Debug for Timeout<B, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv, K: Clock> fmt::Debug for Delay<B, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv, K: Clock> fmt::Debug for Cooldown<B, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<B: Bhv, K: Clock> fmt::Debug for Throttle<B, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
@@ -0,0 +1,54 @@
+use tracing::span::EnteredSpan;
+
+use crate::{NodeInfo, Status, TreeObserver};
+
+/// A [`TreeObserver`] that reports the lifecycle of nodes through the [`tracing`] crate.
+///
+/// Each update of a node is wrapped in a span named `node`, with the fields `id`, `kind`, `name`
+/// and `path` describing the node. Since span names have to be known at compile time, the kind of
+/// the node or the label given through [`crate::BhvExt::label`] is found in the `kind` and `name`
+/// fields respectively. The status returned by the node is emitted as an event inside of the span,
+/// while resets and halts are emitted as events in the span of the parent node. Everything is
+/// emitted at the `TRACE` level, with `bhv` as target.
+///
+/// Since spans are nested as the nodes are, a tree shows up as nested spans in the subscriber.
+#[derive(Default)]
+pub struct TracingObserver {
+    spans: Vec<EnteredSpan>,
+}
+
+impl TracingObserver {
+    /// Create an observer with no spans entered.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TreeObserver for TracingObserver {
+    fn on_enter(&mut self, node: &NodeInfo<'_>) {
+        let span = tracing::trace_span!(
+            target: "bhv",
+            "node",
+            id = node.id.0,
+            kind = node.kind,
+            name = node.name,
+            path = ?node.path,
+        );
+
+        self.spans.push(span.entered());
+    }
+
+    fn on_status(&mut self, _node: &NodeInfo<'_>, status: Status) {
+        tracing::trace!(target: "bhv", ?status, "status");
+        self.spans.pop();
+    }
+
+    fn on_reset(&mut self, node: &NodeInfo<'_>, status: Status) {
+        tracing::trace!(target: "bhv", id = node.id.0, name = node.name, ?status, "reset");
+    }
+
+    fn on_halt(&mut self, node: &NodeInfo<'_>) {
+        tracing::trace!(target: "bhv", id = node.id.0, name = node.name, "halt");
+    }
+}
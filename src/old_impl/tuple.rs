@@ -0,0 +1,353 @@
+use std::{fmt, marker::PhantomData};
+
+use crate::old_impl::{
+    composite::{SelPolicy, SeqPolicy, StatusPolicy},
+    core::{Bhv, Status},
+    observe,
+};
+use crate::introspect::{NodeMut, NodeRef};
+
+/// A tuple of nodes sharing the same context, used as the children of [`SeqT`] and [`SelT`].
+///
+/// Implemented for tuples of up to 12 nodes. Children are accessed by index through a `match`,
+/// so calls to them are statically dispatched and can be inlined. Unless an observed tree (see
+/// [`crate::TreeObserver`]) is running, the only extra work per call is a single atomic load.
+pub trait BhvTuple {
+    /// The context shared by the nodes.
+    type Context;
+
+    /// The number of nodes in the tuple.
+    const LEN: usize;
+
+    /// Update the node at `index`.
+    fn update_at(&mut self, index: usize, ctx: &mut Self::Context) -> Status;
+
+    /// Reset the node at `index`.
+    fn reset_at(&mut self, index: usize, status: Status);
+
+    /// Halt the node at `index`.
+    fn halt_at(&mut self, index: usize, ctx: &mut Self::Context);
+
+    /// Call `f` on each node, in order.
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>));
+
+    /// Call `f` on each node, in order.
+    fn visit_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>));
+}
+
+/// A list of nodes stored in a tuple, run in order until one of them does not return
+/// `Policy::STATUS`. The statically dispatched version of `List`.
+pub(crate) struct TupleList<T, Policy> {
+    nodes: T,
+    current: usize,
+    _tag: PhantomData<Policy>,
+}
+
+/// A selector whose children are stored in a tuple instead of a list of boxes, so that no
+/// allocations or dynamic calls are needed. Behaves exactly like [`crate::Sel`].
+pub struct SelT<T: BhvTuple>(pub(crate) TupleList<T, SelPolicy>);
+
+/// A sequence whose children are stored in a tuple instead of a list of boxes, so that no
+/// allocations or dynamic calls are needed. Behaves exactly like [`crate::Seq`].
+pub struct SeqT<T: BhvTuple>(pub(crate) TupleList<T, SeqPolicy>);
+
+impl<T: BhvTuple> SelT<T> {
+    #[inline]
+    pub fn new(nodes: T) -> Self {
+        Self(TupleList {
+            nodes,
+            current: 0,
+            _tag: PhantomData,
+        })
+    }
+}
+
+impl<T: BhvTuple> SeqT<T> {
+    #[inline]
+    pub fn new(nodes: T) -> Self {
+        Self(TupleList {
+            nodes,
+            current: 0,
+            _tag: PhantomData,
+        })
+    }
+}
+
+impl<T, Policy> Bhv for TupleList<T, Policy>
+    where
+        T: BhvTuple,
+        Policy: StatusPolicy,
+{
+    type Context = T::Context;
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        loop {
+            if self.current >= T::LEN {
+                self.reset(Policy::STATUS);
+                return Policy::STATUS;
+            } else {
+                let s = self.nodes.update_at(self.current, ctx);
+
+                if s == Policy::STATUS {
+                    self.current += 1;
+                    continue;
+                } else if s == Status::Running {
+                    return Status::Running;
+                } else {
+                    self.reset(s);
+                    return s;
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn reset(&mut self, _status: Status) {
+        for i in 0..self.current.min(T::LEN) {
+            self.nodes.reset_at(i, Policy::STATUS);
+        }
+
+        self.current = 0;
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        if self.current < T::LEN {
+            self.nodes.halt_at(self.current, ctx);
+        }
+
+        self.reset(Policy::STATUS);
+    }
+
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.current as u64]
+    }
+
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        match *state {
+            [current] if (current as usize) < T::LEN => {
+                self.current = current as usize;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<T: BhvTuple> Bhv for SelT<T> {
+    type Context = T::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Sel"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.0.nodes.visit(f)
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.0.nodes.visit_mut(f)
+    }
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        self.0.update(ctx)
+    }
+
+    #[inline]
+    fn reset(&mut self, _status: Status) {
+        self.0.reset(_status)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        self.0.save_state()
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        self.0.load_state(state)
+    }
+}
+
+impl<T: BhvTuple> Bhv for SeqT<T> {
+    type Context = T::Context;
+
+    #[inline]
+    fn kind(&self) -> &'static str {
+        "Seq"
+    }
+
+    #[inline]
+    fn visit_children<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+        self.0.nodes.visit(f)
+    }
+
+    #[inline]
+    fn visit_children_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+        self.0.nodes.visit_mut(f)
+    }
+
+    #[inline]
+    fn update(&mut self, ctx: &mut Self::Context) -> Status {
+        self.0.update(ctx)
+    }
+
+    #[inline]
+    fn reset(&mut self, _status: Status) {
+        self.0.reset(_status)
+    }
+
+    #[inline]
+    fn halt(&mut self, ctx: &mut Self::Context) {
+        self.0.halt(ctx)
+    }
+
+    #[inline]
+    fn save_state(&self) -> Vec<u64> {
+        self.0.save_state()
+    }
+
+    #[inline]
+    fn load_state(&mut self, state: &[u64]) -> bool {
+        self.0.load_state(state)
+    }
+}
+
+impl<T: BhvTuple> fmt::Debug for SelT<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+impl<T: BhvTuple> fmt::Debug for SeqT<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NodeRef::new(self).fmt(f)
+    }
+}
+
+macro_rules! impl_bhv_tuple {
+    ($len:expr; $($i:tt $t:ident),+) => {
+        impl<Ctx, $($t),+> BhvTuple for ($($t,)+)
+            where
+                $($t: Bhv<Context=Ctx>),+
+        {
+            type Context = Ctx;
+
+            const LEN: usize = $len;
+
+            #[inline]
+            fn update_at(&mut self, index: usize, ctx: &mut Ctx) -> Status {
+                match index {
+                    $($i => observe::update($i, &mut self.$i, ctx),)+
+                    _ => panic!("index {} out of bounds for a tuple of {} nodes", index, $len),
+                }
+            }
+
+            #[inline]
+            fn reset_at(&mut self, index: usize, status: Status) {
+                match index {
+                    $($i => observe::reset($i, &mut self.$i, status),)+
+                    _ => panic!("index {} out of bounds for a tuple of {} nodes", index, $len),
+                }
+            }
+
+            #[inline]
+            fn halt_at(&mut self, index: usize, ctx: &mut Ctx) {
+                match index {
+                    $($i => observe::halt($i, &mut self.$i, ctx),)+
+                    _ => panic!("index {} out of bounds for a tuple of {} nodes", index, $len),
+                }
+            }
+
+            #[inline]
+            fn visit<'a>(&'a self, f: &mut dyn FnMut(NodeRef<'a>)) {
+                $(f(NodeRef::new(&self.$i));)+
+            }
+
+            #[inline]
+            fn visit_mut(&mut self, f: &mut dyn FnMut(NodeMut<'_>)) {
+                $(f(NodeMut::new(&mut self.$i));)+
+            }
+        }
+    };
+}
+
+impl_bhv_tuple!(1; 0 A);
+impl_bhv_tuple!(2; 0 A, 1 B);
+impl_bhv_tuple!(3; 0 A, 1 B, 2 C);
+impl_bhv_tuple!(4; 0 A, 1 B, 2 C, 3 D);
+impl_bhv_tuple!(5; 0 A, 1 B, 2 C, 3 D, 4 E);
+impl_bhv_tuple!(6; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F);
+impl_bhv_tuple!(7; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G);
+impl_bhv_tuple!(8; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H);
+impl_bhv_tuple!(9; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I);
+impl_bhv_tuple!(10; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J);
+impl_bhv_tuple!(11; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K);
+impl_bhv_tuple!(12; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L);
+
+/// A macro used to create a statically dispatched selector ([`SelT`]) from up to 12 behaviors.
+/// It behaves exactly like [`crate::sel!`], without boxing the behaviors.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let tree = sel_t! {
+///     seq_t! {
+///         cond(|v: &i32| *v < 10),
+///         action(|v: &mut i32| *v *= 2),
+///     },
+///     action(|v: &mut i32| *v = 0),
+/// };
+///
+/// let mut ctx = 4;
+/// assert!(tree.execute(&mut ctx));
+/// assert_eq!(ctx, 8);
+/// ```
+#[macro_export]
+macro_rules! sel_t {
+    () => {
+        compile_error!("`sel_t` should have at least one argument!")
+    };
+    ($($x:expr),+$(,)?) => {
+        $crate::SelT::new(($($x,)+))
+    };
+}
+
+/// A macro used to create a statically dispatched sequence ([`SeqT`]) from up to 12 behaviors.
+/// It behaves exactly like [`crate::seq!`], without boxing the behaviors.
+///
+/// # Example
+///
+/// ```
+/// use bhv::*;
+///
+/// let tree = seq_t! {
+///     cond(|v: &i32| *v > 10), // only run if v > 10
+///     action(|v: &mut i32| *v -= 10),
+/// };
+///
+/// let mut ctx = 11;
+/// assert!(tree.execute(&mut ctx));
+/// assert_eq!(ctx, 1);
+/// ```
+#[macro_export]
+macro_rules! seq_t {
+    () => {
+        compile_error!("`seq_t` should have at least one argument!")
+    };
+    ($($x:expr),+$(,)?) => {
+        $crate::SeqT::new(($($x,)+))
+    };
+}